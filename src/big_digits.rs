@@ -0,0 +1,52 @@
+//! Large block-character rendering for the main timer.
+
+use ratatui::text::Line;
+
+/// Rows in every glyph.
+pub const HEIGHT: usize = 5;
+
+/// Blank columns between glyphs.
+const GAP: usize = 1;
+
+fn glyph(c: char) -> Option<[&'static str; HEIGHT]> {
+    Some(match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["██ ", " █ ", " █ ", " █ ", "███"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        '.' => [" ", " ", " ", " ", "█"],
+        's' => ["   ", " ██", "█▄ ", " ▀█", "██ "],
+        'm' => ["     ", "     ", "█▀▄▀█", "█ █ █", "█   █"],
+        'h' => ["█  ", "█  ", "███", "█ █", "█ █"],
+        'd' => ["  █", "  █", "███", "█ █", "███"],
+        ' ' => ["  ", "  ", "  ", "  ", "  "],
+        _ => return None,
+    })
+}
+
+/// Width in columns of `s` when drawn with `big_digits`.
+pub fn width(s: &str) -> usize {
+    let glyphs: Vec<_> = s.chars().filter_map(glyph).collect();
+    let glyph_width: usize = glyphs.iter().map(|g| g[0].chars().count()).sum();
+    glyph_width + GAP * glyphs.len().saturating_sub(1)
+}
+
+/// Renders `s` as `HEIGHT` lines of block characters. Characters without a
+/// glyph are skipped.
+pub fn big_digits(s: &str) -> Vec<Line<'static>> {
+    let glyphs: Vec<_> = s.chars().filter_map(glyph).collect();
+    let gap = " ".repeat(GAP);
+    (0..HEIGHT)
+        .map(|row| {
+            let text: Vec<&str> = glyphs.iter().map(|g| g[row]).collect();
+            Line::from(text.join(&gap))
+        })
+        .collect()
+}
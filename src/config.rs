@@ -0,0 +1,504 @@
+//! Optional user configuration, read from `config.toml`.
+//!
+//! Every section and field may be left out; anything missing falls back to
+//! the built-in defaults.
+
+use crate::keys::Action;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use stopwatch::{parse_duration, DisplayFormat, PersonalBest, Pomodoro, Precision};
+
+/// Timer color thresholds used when the config doesn't override them.
+const DEFAULT_TIMER_THRESHOLDS: [u64; 3] = [10, 60, 300];
+
+/// The built-in color themes, in the order they're cycled through.
+pub const PALETTES: &[Palette] = &[
+    Palette {
+        name: "default",
+        title: Color::Cyan,
+        accent: Color::Yellow,
+        text: Color::White,
+        muted: Color::Gray,
+        dim: Color::DarkGray,
+        good: Color::Green,
+        warn: Color::Yellow,
+        bad: Color::Red,
+        highlight: Style::new()
+            .fg(Color::White)
+            .bg(Color::Blue)
+            .add_modifier(Modifier::BOLD),
+        selection_marker: false,
+        good_modifier: Modifier::empty(),
+        bad_modifier: Modifier::empty(),
+        timer_colors: [Color::Green, Color::Yellow, Color::Cyan, Color::Magenta],
+    },
+    // Only the terminal's own colors, with reverse video for the selection and
+    // the slowest lap, and bold for the fastest
+    Palette {
+        name: "mono",
+        title: Color::Reset,
+        accent: Color::Reset,
+        text: Color::Reset,
+        muted: Color::Reset,
+        dim: Color::Reset,
+        good: Color::Reset,
+        warn: Color::Reset,
+        bad: Color::Reset,
+        highlight: Style::new().add_modifier(Modifier::REVERSED.union(Modifier::BOLD)),
+        selection_marker: false,
+        good_modifier: Modifier::BOLD,
+        bad_modifier: Modifier::REVERSED,
+        timer_colors: [Color::Reset; 4],
+    },
+    Palette {
+        name: "solarized",
+        title: Color::Rgb(0x2a, 0xa1, 0x98),
+        accent: Color::Rgb(0xb5, 0x89, 0x00),
+        text: Color::Rgb(0x93, 0xa1, 0xa1),
+        muted: Color::Rgb(0x83, 0x94, 0x96),
+        dim: Color::Rgb(0x58, 0x6e, 0x75),
+        good: Color::Rgb(0x85, 0x99, 0x00),
+        warn: Color::Rgb(0xcb, 0x4b, 0x16),
+        bad: Color::Rgb(0xdc, 0x32, 0x2f),
+        highlight: Style::new()
+            .fg(Color::Rgb(0xfd, 0xf6, 0xe3))
+            .bg(Color::Rgb(0x26, 0x8b, 0xd2))
+            .add_modifier(Modifier::BOLD),
+        selection_marker: false,
+        good_modifier: Modifier::empty(),
+        bad_modifier: Modifier::empty(),
+        timer_colors: [
+            Color::Rgb(0x85, 0x99, 0x00),
+            Color::Rgb(0xb5, 0x89, 0x00),
+            Color::Rgb(0x2a, 0xa1, 0x98),
+            Color::Rgb(0xd3, 0x36, 0x82),
+        ],
+    },
+    // Reverse video and a gutter marker for the selection, in case a background
+    // color doesn't show up
+    Palette {
+        name: "high-contrast",
+        title: Color::LightCyan,
+        accent: Color::LightYellow,
+        text: Color::White,
+        muted: Color::White,
+        dim: Color::Gray,
+        good: Color::LightGreen,
+        warn: Color::LightYellow,
+        bad: Color::LightRed,
+        highlight: Style::new().add_modifier(Modifier::REVERSED.union(Modifier::BOLD)),
+        selection_marker: true,
+        good_modifier: Modifier::empty(),
+        bad_modifier: Modifier::empty(),
+        timer_colors: [
+            Color::LightGreen,
+            Color::LightYellow,
+            Color::LightCyan,
+            Color::LightMagenta,
+        ],
+    },
+];
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub timer: TimerConfig,
+    pub pomodoro: PomodoroConfig,
+    /// The `[keys]` section: actions to rebind, each to one key or a list of them.
+    ///
+    /// ```toml
+    /// [keys]
+    /// lap = "enter"
+    /// quit = ["x", "esc"]
+    /// ```
+    pub keys: HashMap<Action, KeyList>,
+}
+
+/// One key name or several, for a `[keys]` entry.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The `[timer]` section.
+///
+/// ```toml
+/// [timer]
+/// thresholds = [10, 60, 300]                      # seconds
+/// colors = ["green", "yellow", "cyan", "magenta"] # one more than thresholds
+/// milestone_interval = 60                         # seconds, off when left out
+/// tick_rate = "50ms"                              # redraw interval, 10ms-1s
+/// format = "words"                                # or "clock" for 00:01:05.00
+/// precision = "hundredths"                        # or "tenths", "milliseconds"
+/// lap_precision = "milliseconds"                  # laps only, same as precision when left out
+/// personal_best = "average"                       # or "laps" for the most laps
+/// theme = "default"                               # or "mono", "solarized", "high-contrast"
+/// second_bar = true                               # bar filling up over each second
+/// selection_marker = true                         # `>` beside the selected lap, theme's choice unless set
+/// window = 5                                      # laps in the rolling average
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    pub thresholds: Option<Vec<u64>>,
+    pub colors: Option<Vec<String>>,
+    pub milestone_interval: Option<u64>,
+    pub tick_rate: Option<String>,
+    pub format: Option<String>,
+    pub precision: Option<String>,
+    pub lap_precision: Option<String>,
+    pub personal_best: Option<String>,
+    pub theme: Option<String>,
+    pub second_bar: Option<bool>,
+    pub window: Option<usize>,
+    pub selection_marker: Option<bool>,
+}
+
+/// The `[pomodoro]` section, used with `--pomodoro`.
+///
+/// ```toml
+/// [pomodoro]
+/// work = "25m"
+/// short_break = "5m"
+/// long_break = "15m"
+/// long_break_every = 4 # work sessions
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PomodoroConfig {
+    pub work: Option<String>,
+    pub short_break: Option<String>,
+    pub long_break: Option<String>,
+    pub long_break_every: Option<u32>,
+}
+
+impl PomodoroConfig {
+    /// The configured intervals, with the defaults for anything left out.
+    pub fn pomodoro(&self) -> Result<Pomodoro, String> {
+        let defaults = Pomodoro::default();
+        let interval = |field: &str, value: &Option<String>, default: Duration| {
+            let Some(text) = value else {
+                return Ok(default);
+            };
+            match parse_duration(text) {
+                Ok(duration) if !duration.is_zero() => Ok(duration),
+                Ok(_) => Err(format!("pomodoro.{} must be longer than zero", field)),
+                Err(err) => Err(format!("invalid duration for pomodoro.{}: {}", field, err)),
+            }
+        };
+
+        let long_break_every = self.long_break_every.unwrap_or(defaults.long_break_every);
+        if long_break_every == 0 {
+            return Err("pomodoro.long_break_every must be at least 1".to_string());
+        }
+        Ok(Pomodoro::new(
+            interval("work", &self.work, defaults.work)?,
+            interval("short_break", &self.short_break, defaults.short_break)?,
+            interval("long_break", &self.long_break, defaults.long_break)?,
+            long_break_every,
+        ))
+    }
+}
+
+fn parse_precision(field: &str, value: &Option<String>) -> Result<Option<Precision>, String> {
+    value
+        .as_deref()
+        .map(|level| {
+            Precision::parse(level).ok_or_else(|| {
+                format!(
+                    "{} must be \"tenths\", \"hundredths\" or \"milliseconds\", not {:?}",
+                    field, level
+                )
+            })
+        })
+        .transpose()
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/stopwatch/config.toml`, falling back to `~/.config`.
+    pub fn path() -> Option<PathBuf> {
+        let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(config_dir.join("stopwatch").join("config.toml"))
+    }
+
+    /// How often the timer marks a milestone, if at all.
+    pub fn milestone_interval(&self) -> Result<Option<Duration>, String> {
+        match self.timer.milestone_interval {
+            Some(0) => Err("timer.milestone_interval must be at least 1".to_string()),
+            interval => Ok(interval.map(Duration::from_secs)),
+        }
+    }
+
+    /// How often a running timer is redrawn, if set.
+    pub fn tick_rate(&self) -> Result<Option<Duration>, String> {
+        self.timer
+            .tick_rate
+            .as_deref()
+            .map(|text| {
+                parse_duration(text)
+                    .map_err(|err| format!("invalid duration for timer.tick_rate: {}", err))
+            })
+            .transpose()
+    }
+
+    /// How durations are written out, words unless set.
+    pub fn display_format(&self) -> Result<DisplayFormat, String> {
+        match &self.timer.format {
+            None => Ok(DisplayFormat::Words),
+            Some(format) => DisplayFormat::parse(format).ok_or_else(|| {
+                format!(
+                    "timer.format must be \"words\" or \"clock\", not {:?}",
+                    format
+                )
+            }),
+        }
+    }
+
+    /// Fractional seconds on the timer, if set.
+    pub fn precision(&self) -> Result<Option<Precision>, String> {
+        parse_precision("timer.precision", &self.timer.precision)
+    }
+
+    /// Fractional seconds on laps, if set apart from the timer's.
+    pub fn lap_precision(&self) -> Result<Option<Precision>, String> {
+        parse_precision("timer.lap_precision", &self.timer.lap_precision)
+    }
+
+    /// Whether a running timer has a bar filling up over each second, on unless set.
+    pub fn second_bar(&self) -> bool {
+        self.timer.second_bar.unwrap_or(true)
+    }
+
+    /// How many of the latest laps the rolling average covers, 5 unless set.
+    pub fn window(&self) -> Result<usize, String> {
+        match self.timer.window {
+            Some(0) => Err("timer.window must be at least 1".to_string()),
+            window => Ok(window.unwrap_or(5)),
+        }
+    }
+
+    /// Which record the statistics panel shows, the fastest average lap unless set.
+    pub fn personal_best(&self) -> Result<PersonalBest, String> {
+        match &self.timer.personal_best {
+            None => Ok(PersonalBest::Average),
+            Some(kind) => PersonalBest::parse(kind).ok_or_else(|| {
+                format!(
+                    "timer.personal_best must be \"average\" or \"laps\", not {:?}",
+                    kind
+                )
+            }),
+        }
+    }
+
+    /// Loads the config file from `flag` (`--config`), then `$STOPWATCH_CONFIG`,
+    /// then `Config::path`. Only the default location may be missing, giving the defaults.
+    pub fn load(flag: Option<&Path>) -> Result<Config, String> {
+        let explicit = flag.map(Path::to_path_buf).or_else(|| {
+            env::var_os("STOPWATCH_CONFIG")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+        });
+        let (path, required) = match explicit {
+            Some(path) => (path, true),
+            None => match Config::path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !required => Ok(Config::default()),
+            Err(err) => Err(format!("{}: {}", path.display(), err)),
+        }
+    }
+}
+
+/// Colors for each part of the UI, making up one of the `PALETTES`.
+#[derive(Clone, Copy)]
+pub struct Palette {
+    pub name: &'static str,
+    pub title: Color,         // Title, tabs and the lap time graph
+    pub accent: Color,        // Lap names and keys in the help
+    pub text: Color,          // Input and ordinary laps
+    pub muted: Color,         // Hints, headings and secondary times
+    pub dim: Color,           // Borders of a paused timer
+    pub good: Color,          // Faster laps and an early countdown
+    pub warn: Color,          // Status messages and a countdown halfway through
+    pub bad: Color,           // Slower laps, alerts and confirmations
+    pub highlight: Style,     // The selected lap
+    selection_marker: bool,   // Also mark the selected lap with `>` in a gutter
+    good_modifier: Modifier,  // Added to `good` laps, for when the color alone doesn't show
+    bad_modifier: Modifier,   // Added to `bad` laps, likewise
+    timer_colors: [Color; 4], // For the default timer thresholds
+}
+
+impl Palette {
+    /// The fastest lap and faster deltas.
+    pub fn good_style(&self) -> Style {
+        Style::new().fg(self.good).add_modifier(self.good_modifier)
+    }
+
+    /// The slowest lap and slower deltas.
+    pub fn bad_style(&self) -> Style {
+        Style::new().fg(self.bad).add_modifier(self.bad_modifier)
+    }
+}
+
+/// Colors used to draw the UI.
+pub struct Theme {
+    /// The colors in use, switched at runtime with `next_palette`.
+    pub palette: Palette,
+    /// Colors are turned off, so only `mono` is ever used.
+    colors_off: bool,
+    /// Ascending elapsed times at which the timer switches to the next color.
+    timer_thresholds: Vec<Duration>,
+    /// One color per band, so always one longer than `timer_thresholds`. Left
+    /// out to use the palette's.
+    timer_colors: Option<Vec<Color>>,
+    /// Whether the selected lap gets a `>` marker whatever the palette says.
+    selection_marker: Option<bool>,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            palette: PALETTES[0],
+            colors_off: false,
+            timer_thresholds: DEFAULT_TIMER_THRESHOLDS
+                .iter()
+                .map(|&secs| Duration::from_secs(secs))
+                .collect(),
+            timer_colors: None,
+            selection_marker: None,
+        }
+    }
+}
+
+impl Theme {
+    /// The configured theme. Colors are turned off, leaving `mono`, by `no_color`
+    /// (`--no-color`), or when no theme is configured and either `$NO_COLOR` is
+    /// set or `$TERM` is `dumb`.
+    pub fn from_config(config: &Config, no_color: bool) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+            || env::var_os("TERM").is_some_and(|term| term == "dumb");
+        theme.colors_off = no_color || (config.timer.theme.is_none() && no_color_env);
+        let name = if theme.colors_off {
+            Some("mono")
+        } else {
+            config.timer.theme.as_deref()
+        };
+        if let Some(name) = name {
+            theme.palette = *PALETTES
+                .iter()
+                .find(|palette| palette.name == name)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = PALETTES.iter().map(|palette| palette.name).collect();
+                    format!(
+                        "timer.theme must be one of {}, not {:?}",
+                        names.join(", "),
+                        name
+                    )
+                })?;
+        }
+
+        if let Some(thresholds) = &config.timer.thresholds {
+            if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err("timer.thresholds must be in ascending order".to_string());
+            }
+            theme.timer_thresholds = thresholds
+                .iter()
+                .map(|&secs| Duration::from_secs(secs))
+                .collect();
+        }
+        if let Some(colors) = &config.timer.colors {
+            theme.timer_colors = Some(
+                colors
+                    .iter()
+                    .map(|name| {
+                        Color::from_str(name).map_err(|_| format!("unknown color: {}", name))
+                    })
+                    .collect::<Result<_, _>>()?,
+            );
+        }
+
+        let bands = theme
+            .timer_colors
+            .as_ref()
+            .map_or(theme.palette.timer_colors.len(), Vec::len);
+        if bands != theme.timer_thresholds.len() + 1 {
+            return Err(format!(
+                "timer.colors needs {} entries, one more than timer.thresholds",
+                theme.timer_thresholds.len() + 1
+            ));
+        }
+        if theme.colors_off {
+            theme.timer_colors = None;
+        }
+        theme.selection_marker = config.timer.selection_marker;
+        Ok(theme)
+    }
+
+    /// Color of the main timer after `elapsed` time.
+    pub fn timer_color(&self, elapsed: Duration) -> Color {
+        let band = self
+            .timer_thresholds
+            .iter()
+            .take_while(|&&threshold| elapsed >= threshold)
+            .count();
+        match &self.timer_colors {
+            Some(colors) => colors[band],
+            None => self.palette.timer_colors[band],
+        }
+    }
+
+    /// Whether the selected lap is marked with `>` as well as highlighted: the
+    /// configured `selection_marker`, or the palette's choice.
+    pub fn selection_marker(&self) -> bool {
+        self.selection_marker
+            .unwrap_or(self.palette.selection_marker)
+    }
+
+    /// Switches to the palette called `name`. Configured timer colors are kept.
+    pub fn set_palette(&mut self, name: &str) -> Result<(), String> {
+        if self.colors_off {
+            return Err("Colors are turned off".to_string());
+        }
+        self.palette = *PALETTES
+            .iter()
+            .find(|palette| palette.name == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = PALETTES.iter().map(|palette| palette.name).collect();
+                format!("Unknown theme {}, expected {}", name, names.join(", "))
+            })?;
+        Ok(())
+    }
+
+    /// Switches to the palette after the current one in `PALETTES`, wrapping
+    /// around. Configured timer colors are kept. Returns false, changing nothing,
+    /// when colors are turned off.
+    pub fn next_palette(&mut self) -> bool {
+        if self.colors_off {
+            return false;
+        }
+        let current = PALETTES
+            .iter()
+            .position(|palette| palette.name == self.palette.name)
+            .unwrap_or(0);
+        self.palette = PALETTES[(current + 1) % PALETTES.len()];
+        true
+    }
+}
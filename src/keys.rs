@@ -0,0 +1,266 @@
+//! Key bindings, with defaults that can be remapped in the `[keys]` config section.
+
+use crate::config::{Config, KeyList};
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Something a key can be bound to. Named in snake case in the config, e.g. `delete_lap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Lap,
+    Pause,
+    Reset,
+    ResetClock,
+    DeleteLap,
+    ClearLaps,
+    Undo,
+    Export,
+    ExportMarkdown,
+    Import,
+    CopyLap,
+    CopyAllLaps,
+    CopyElapsed,
+    CopyCsv,
+    ToggleStats,
+    ToggleGraph,
+    ToggleBigDigits,
+    ToggleMinimal,
+    Freeze,
+    ToggleFormat,
+    CycleTheme,
+    ToggleBell,
+    ToggleFollow,
+    SetAlarm,
+    ExtendCountdown,
+    ShortenCountdown,
+    NameSession,
+    LabelLap,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    NewestLap,
+    OldestLap,
+    FastestLap,
+    SlowestLap,
+    SortLaps,
+    ReferenceLap,
+    NextTimer,
+    PreviousTimer,
+    AddTimer,
+    RemoveTimer,
+    Command,
+    Help,
+    Quit,
+}
+
+/// Keys for every action when the config doesn't change them.
+pub const DEFAULT_KEY_BINDINGS: &[(Action, &[KeyCode])] = &[
+    (Action::Lap, &[KeyCode::Char(' ')]),
+    (Action::Pause, &[KeyCode::Char('p')]),
+    (Action::Reset, &[KeyCode::Char('r')]),
+    (Action::ResetClock, &[KeyCode::Char('R')]),
+    (Action::DeleteLap, &[KeyCode::Char('d')]),
+    (Action::ClearLaps, &[KeyCode::Char('L')]),
+    (Action::Undo, &[KeyCode::Char('u')]),
+    (Action::Export, &[KeyCode::Char('e')]),
+    (Action::ExportMarkdown, &[KeyCode::Char('m')]),
+    (Action::Import, &[KeyCode::Char('i')]),
+    (Action::CopyLap, &[KeyCode::Char('c')]),
+    (Action::CopyAllLaps, &[KeyCode::Char('C')]),
+    (Action::CopyElapsed, &[KeyCode::Char('y')]),
+    (Action::CopyCsv, &[KeyCode::Char('Y')]),
+    (Action::ToggleStats, &[KeyCode::Char('s')]),
+    (Action::ToggleGraph, &[KeyCode::Char('g')]),
+    (Action::ToggleBigDigits, &[KeyCode::Char('b')]),
+    (Action::ToggleMinimal, &[KeyCode::Char('z')]),
+    (Action::Freeze, &[KeyCode::Char('x')]),
+    (Action::ToggleFormat, &[KeyCode::Char('T')]),
+    (Action::CycleTheme, &[KeyCode::Char('h')]),
+    (Action::ToggleBell, &[KeyCode::Char('B')]),
+    (Action::ToggleFollow, &[KeyCode::Char('w')]),
+    (Action::SetAlarm, &[KeyCode::Char('a')]),
+    (Action::ExtendCountdown, &[KeyCode::Char(']')]),
+    (Action::ShortenCountdown, &[KeyCode::Char('[')]),
+    (Action::NameSession, &[KeyCode::Char('n')]),
+    (Action::LabelLap, &[KeyCode::Char('t')]),
+    (Action::ScrollUp, &[KeyCode::Up, KeyCode::Char('k')]),
+    (Action::ScrollDown, &[KeyCode::Down, KeyCode::Char('j')]),
+    (Action::PageUp, &[KeyCode::PageUp]),
+    (Action::PageDown, &[KeyCode::PageDown]),
+    (Action::NewestLap, &[KeyCode::Home]),
+    (Action::OldestLap, &[KeyCode::End]),
+    (Action::FastestLap, &[KeyCode::Char('f')]),
+    (Action::SlowestLap, &[KeyCode::Char('F')]),
+    (Action::SortLaps, &[KeyCode::Char('o')]),
+    (Action::ReferenceLap, &[KeyCode::Char('v')]),
+    (Action::NextTimer, &[KeyCode::Tab]),
+    (Action::PreviousTimer, &[KeyCode::BackTab]),
+    (Action::AddTimer, &[KeyCode::Char('+')]),
+    (Action::RemoveTimer, &[KeyCode::Char('-')]),
+    (Action::Command, &[KeyCode::Char(':')]),
+    (Action::Help, &[KeyCode::Char('?')]),
+    (Action::Quit, &[KeyCode::Char('q'), KeyCode::Esc]),
+];
+
+/// Which keys trigger which actions.
+pub struct KeyBindings {
+    /// Every action in `DEFAULT_KEY_BINDINGS` order, with its keys.
+    keys: Vec<(Action, Vec<KeyCode>)>,
+    actions: HashMap<KeyCode, Action>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> KeyBindings {
+        let keys = DEFAULT_KEY_BINDINGS
+            .iter()
+            .map(|&(action, keys)| (action, keys.to_vec()))
+            .collect();
+        KeyBindings::new(keys).expect("default key bindings conflict")
+    }
+}
+
+impl KeyBindings {
+    fn new(keys: Vec<(Action, Vec<KeyCode>)>) -> Result<KeyBindings, String> {
+        let mut actions = HashMap::new();
+        for (action, codes) in &keys {
+            for &code in codes {
+                if let Some(other) = actions.insert(code, *action) {
+                    if other != *action {
+                        return Err(format!(
+                            "key {} is bound to both {:?} and {:?}",
+                            key_label(code),
+                            other,
+                            action
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(KeyBindings { keys, actions })
+    }
+
+    /// The defaults, with any actions listed in `[keys]` replaced.
+    pub fn from_config(config: &Config) -> Result<KeyBindings, String> {
+        let mut keys = KeyBindings::default().keys;
+        for (action, codes) in &mut keys {
+            let Some(list) = config.keys.get(action) else {
+                continue;
+            };
+            let names = match list {
+                KeyList::One(name) => std::slice::from_ref(name),
+                KeyList::Many(names) => names.as_slice(),
+            };
+            *codes = names
+                .iter()
+                .map(|name| parse_key(name).ok_or_else(|| format!("unknown key: {}", name)))
+                .collect::<Result<_, _>>()?;
+        }
+        KeyBindings::new(keys)
+    }
+
+    /// The action bound to `code`, if any.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.actions.get(&code).copied()
+    }
+
+    /// Every key bound to `action`, for display, e.g. `↑ / K`.
+    pub fn label(&self, action: Action) -> String {
+        let codes = self
+            .keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[][..], |(_, codes)| codes.as_slice());
+        if codes.is_empty() {
+            return "unbound".to_string();
+        }
+        codes
+            .iter()
+            .map(|&code| key_label(code))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// The first key bound to `action`, if any.
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .and_then(|(_, codes)| codes.first().copied())
+    }
+
+    /// The first key bound to `action`, for short hints like the controls bar.
+    pub fn short_label(&self, action: Action) -> String {
+        self.key(action)
+            .map_or_else(|| "unbound".to_string(), key_label)
+    }
+}
+
+/// Parses a key name from the config: a single character (case matters, so `L`
+/// is Shift+L), `shift+<letter>`, or a named key such as `enter` or `pageup`.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let lower = name.to_ascii_lowercase();
+    if let Some(letter) = lower.strip_prefix("shift+") {
+        let mut chars = letter.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c.is_ascii_alphabetic() {
+                return Some(KeyCode::Char(c.to_ascii_uppercase()));
+            }
+        }
+    }
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        return (1..=12).contains(&n).then_some(KeyCode::F(n));
+    }
+    Some(match lower.as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" | "shift+tab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return None,
+    })
+}
+
+/// How a key is written in the help overlay and controls bar.
+fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "SPACE".to_string(),
+        KeyCode::Char(c) if c.is_ascii_uppercase() => format!("SHIFT+{}", c),
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::Enter => "ENTER".to_string(),
+        KeyCode::Esc => "ESC".to_string(),
+        KeyCode::Tab => "TAB".to_string(),
+        KeyCode::BackTab => "SHIFT+TAB".to_string(),
+        KeyCode::Backspace => "BACKSPACE".to_string(),
+        KeyCode::Delete => "DEL".to_string(),
+        KeyCode::Insert => "INS".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PGUP".to_string(),
+        KeyCode::PageDown => "PGDN".to_string(),
+        KeyCode::Home => "HOME".to_string(),
+        KeyCode::End => "END".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
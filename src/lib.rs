@@ -0,0 +1,1595 @@
+//! Timing and lap logic behind the stopwatch TUI.
+//!
+//! Nothing in here depends on the terminal, so it can be reused by other
+//! front ends. The binary in `main.rs` layers the ratatui interface on top.
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Whether the clock counts up or down.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Mode {
+    Stopwatch,
+    Countdown { target: Duration },
+}
+
+/// How many fractional digits of a second are displayed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision {
+    Tenths,
+    Hundredths,
+    Milliseconds,
+}
+
+impl Precision {
+    pub fn digits(self) -> usize {
+        match self {
+            Precision::Tenths => 1,
+            Precision::Hundredths => 2,
+            Precision::Milliseconds => 3,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Precision> {
+        match s {
+            "tenths" => Some(Precision::Tenths),
+            "hundredths" => Some(Precision::Hundredths),
+            "milliseconds" | "ms" => Some(Precision::Milliseconds),
+            _ => None,
+        }
+    }
+}
+
+/// How durations are written out: `5m 3.20s` or `00:05:03.20`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayFormat {
+    Words,
+    Clock,
+}
+
+impl DisplayFormat {
+    pub fn parse(s: &str) -> Option<DisplayFormat> {
+        match s {
+            "words" => Some(DisplayFormat::Words),
+            "clock" => Some(DisplayFormat::Clock),
+            _ => None,
+        }
+    }
+
+    /// Formats `duration` with `format_duration` or `format_clock`.
+    pub fn format(self, duration: Duration, precision: Precision) -> String {
+        match self {
+            DisplayFormat::Words => format_duration(duration, precision),
+            DisplayFormat::Clock => format_clock(duration, precision),
+        }
+    }
+}
+
+/// What counts as a personal best in `Records`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PersonalBest {
+    /// The lowest average lap time of any session.
+    Average,
+    /// The most laps recorded in one session.
+    Laps,
+}
+
+impl PersonalBest {
+    pub fn parse(s: &str) -> Option<PersonalBest> {
+        match s {
+            "average" => Some(PersonalBest::Average),
+            "laps" => Some(PersonalBest::Laps),
+            _ => None,
+        }
+    }
+}
+
+/// Which part of a Pomodoro cycle is running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Alternating work and break intervals, with a long break after every
+/// `long_break_every` work sessions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pomodoro {
+    pub work: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+    pub long_break_every: u32,
+    phase: Phase,
+    cycle: u32, // Work session within the current set, counting from 1
+}
+
+impl Default for Pomodoro {
+    fn default() -> Pomodoro {
+        Pomodoro::new(
+            Duration::from_secs(25 * 60),
+            Duration::from_secs(5 * 60),
+            Duration::from_secs(15 * 60),
+            4,
+        )
+    }
+}
+
+impl Pomodoro {
+    /// Starts at the first work session. `long_break_every` is clamped to at least 1.
+    pub fn new(
+        work: Duration,
+        short_break: Duration,
+        long_break: Duration,
+        long_break_every: u32,
+    ) -> Pomodoro {
+        Pomodoro {
+            work,
+            short_break,
+            long_break,
+            long_break_every: long_break_every.max(1),
+            phase: Phase::Work,
+            cycle: 1,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Which work session of the current set this is (or follows, during a break).
+    pub fn cycle(&self) -> u32 {
+        self.cycle
+    }
+
+    /// Length of the current phase.
+    pub fn duration(&self) -> Duration {
+        match self.phase {
+            Phase::Work => self.work,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+
+    /// Moves on to the next phase: work is followed by a break, long once the
+    /// set is complete, and every break by the next work session.
+    pub fn advance(&mut self) {
+        self.phase = match self.phase {
+            Phase::Work if self.cycle >= self.long_break_every => Phase::LongBreak,
+            Phase::Work => Phase::ShortBreak,
+            Phase::ShortBreak => {
+                self.cycle += 1;
+                Phase::Work
+            }
+            Phase::LongBreak => {
+                self.cycle = 1;
+                Phase::Work
+            }
+        };
+    }
+}
+
+/// A countdown of `duration` that starts over each time it runs out, for
+/// `rounds` rounds or, without a limit, indefinitely.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interval {
+    pub duration: Duration,
+    pub rounds: Option<u32>,
+    round: u32, // Round in progress, counting from 1
+    done: bool, // The last round has run out
+}
+
+impl Interval {
+    /// Starts at the first round. A limit of zero rounds is treated as one.
+    pub fn new(duration: Duration, rounds: Option<u32>) -> Interval {
+        Interval {
+            duration,
+            rounds: rounds.map(|rounds| rounds.max(1)),
+            round: 1,
+            done: false,
+        }
+    }
+
+    /// The round in progress, or the last one once they're all done.
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Whether every round has been completed.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Counts the round in progress as completed, returning whether another
+    /// one should start.
+    pub fn advance(&mut self) -> bool {
+        if self.done {
+            return false;
+        }
+        if self.rounds.is_some_and(|rounds| self.round >= rounds) {
+            self.done = true;
+            return false;
+        }
+        self.round += 1;
+        true
+    }
+}
+
+/// Summary of the recorded laps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LapStats {
+    pub count: usize,
+    pub total: Duration,
+    pub avg: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// Laps slower than `avg`. Laps exactly on it count as neither.
+    pub above_avg: usize,
+    /// Laps faster than `avg`.
+    pub below_avg: usize,
+    /// Population standard deviation of the lap times; zero for a single lap.
+    pub std_dev: Duration,
+    /// `std_dev` as a fraction of `avg`, for comparing consistency across paces.
+    /// `None` with a single lap or a zero average, where it means nothing.
+    pub cv: Option<f64>,
+}
+
+/// On-disk form of a `Stopwatch`. `Instant`s can't be serialized, so the clock is
+/// stored as an elapsed duration and rebuilt relative to `Instant::now()` on load.
+#[derive(Serialize, Deserialize)]
+struct Session {
+    #[serde(default)]
+    name: Option<String>,
+    mode: Mode,
+    laps: Vec<(Duration, Duration)>,
+    #[serde(default)]
+    lap_labels: Vec<Option<String>>,
+    #[serde(default)]
+    lap_timestamps: Vec<Option<SystemTime>>,
+    #[serde(default)]
+    lap_phases: Vec<usize>,
+    #[serde(default)]
+    phase: usize,
+    #[serde(default)]
+    start_lap: bool,
+    elapsed: Duration,
+    is_running: bool,
+    #[serde(default)]
+    paused: Duration,
+    /// Made with `new_paused` and never started since.
+    #[serde(default)]
+    unstarted: bool,
+}
+
+/// Either form a session file can take.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SessionFile {
+    One(Session),
+    Many(Vec<Session>),
+}
+
+/// A running or paused clock and the laps recorded on it.
+///
+/// Cloning keeps the same reference `Instant`s, so a clone of a running
+/// stopwatch keeps counting alongside the original.
+#[derive(Clone)]
+pub struct Stopwatch {
+    name: Option<String>,
+    mode: Mode,
+    start_time: Instant,
+    is_running: bool,
+    laps: Vec<(Duration, Duration)>,         // (lap_time, total_time)
+    lap_labels: Vec<Option<String>>,         // One per lap
+    lap_timestamps: Vec<Option<SystemTime>>, // One per lap; none for laps from older sessions
+    lap_phases: Vec<usize>,                  // One per lap: the phase it was recorded in
+    phase: usize,                            // Times resumed since the start, numbered from 0
+    start_lap: bool,                         // Lap 1 is the zero-length lap from `add_start_lap`
+    last_lap: Instant,
+    pause_time: Option<Instant>,
+    paused: Duration, // Finished pauses only; see `total_paused`
+    started: bool,    // False from `new_paused` until the first `toggle_pause`
+}
+
+impl Stopwatch {
+    /// Creates a stopwatch that starts running immediately.
+    pub fn new(mode: Mode) -> Stopwatch {
+        let now = Instant::now();
+        Stopwatch {
+            name: None,
+            mode,
+            start_time: now,
+            is_running: true,
+            laps: Vec::new(),
+            lap_labels: Vec::new(),
+            lap_timestamps: Vec::new(),
+            lap_phases: Vec::new(),
+            phase: 0,
+            start_lap: false,
+            last_lap: now,
+            pause_time: None,
+            paused: Duration::ZERO,
+            started: true,
+        }
+    }
+
+    /// Creates a stopwatch that reads zero and waits for `toggle_pause` to start it.
+    pub fn new_paused(mode: Mode) -> Stopwatch {
+        let mut stopwatch = Stopwatch::new(mode);
+        stopwatch.is_running = false;
+        stopwatch.started = false;
+        // Pausing at the start instant keeps elapsed at exactly zero, and resuming
+        // shifts the clock by the whole wait
+        stopwatch.pause_time = Some(stopwatch.start_time);
+        stopwatch
+    }
+
+    /// Optional label for the session, shown in the UI and included in exports.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Sets the session name. Blank names clear it.
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        self.name = (!name.is_empty()).then(|| name.to_string());
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Switches between counting up and down. Elapsed time and laps are kept.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Recorded laps as `(lap_time, total_time)`, oldest first.
+    pub fn laps(&self) -> &[(Duration, Duration)] {
+        &self.laps
+    }
+
+    /// Whether the first lap is the zero-length one from `add_start_lap`.
+    pub fn has_start_lap(&self) -> bool {
+        self.start_lap
+    }
+
+    /// The laps that measure something: every lap but one from `add_start_lap`.
+    pub fn timed_laps(&self) -> &[(Duration, Duration)] {
+        &self.laps[usize::from(self.start_lap)..]
+    }
+
+    /// Optional note on the lap at `index` (oldest first), e.g. `warmup`.
+    pub fn lap_label(&self, index: usize) -> Option<&str> {
+        self.lap_labels.get(index)?.as_deref()
+    }
+
+    /// Wall-clock time the lap at `index` (oldest first) was recorded. Laps from
+    /// sessions saved before timestamps were kept have none.
+    pub fn lap_timestamp(&self, index: usize) -> Option<SystemTime> {
+        *self.lap_timestamps.get(index)?
+    }
+
+    /// Which phase the lap at `index` (oldest first) was recorded in. Phases are
+    /// the stretches of running between pauses, numbered from 0.
+    pub fn lap_phase(&self, index: usize) -> usize {
+        self.lap_phases.get(index).copied().unwrap_or(0)
+    }
+
+    /// Labels the lap at `index`. Blank labels clear it. Does nothing if there's
+    /// no such lap.
+    pub fn set_lap_label(&mut self, index: usize, label: &str) {
+        if let Some(slot) = self.lap_labels.get_mut(index) {
+            let label = label.trim();
+            *slot = (!label.is_empty()).then(|| label.to_string());
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json(path, &self.to_session())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Stopwatch> {
+        let session: Session = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Stopwatch::from_session(session)
+    }
+
+    /// Saves several stopwatches to one file, in order.
+    pub fn save_all(stopwatches: &[Stopwatch], path: &Path) -> io::Result<()> {
+        let sessions: Vec<Session> = stopwatches.iter().map(Stopwatch::to_session).collect();
+        write_json(path, &sessions)
+    }
+
+    /// Loads a file written by `save_all`, or by `save` as a single stopwatch.
+    pub fn load_all(path: &Path) -> io::Result<Vec<Stopwatch>> {
+        let sessions = match serde_json::from_str(&fs::read_to_string(path)?)? {
+            SessionFile::One(session) => vec![session],
+            SessionFile::Many(sessions) => sessions,
+        };
+        sessions.into_iter().map(Stopwatch::from_session).collect()
+    }
+
+    fn to_session(&self) -> Session {
+        Session {
+            name: self.name.clone(),
+            mode: self.mode,
+            laps: self.laps.clone(),
+            lap_labels: self.lap_labels.clone(),
+            lap_timestamps: self.lap_timestamps.clone(),
+            lap_phases: self.lap_phases.clone(),
+            phase: self.phase,
+            start_lap: self.start_lap,
+            elapsed: self.elapsed(),
+            is_running: self.is_running,
+            paused: self.total_paused(),
+            unstarted: !self.started,
+        }
+    }
+
+    fn from_session(session: Session) -> io::Result<Stopwatch> {
+        let now = Instant::now();
+        let start_time = now.checked_sub(session.elapsed).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "saved elapsed time is too large",
+            )
+        })?;
+        let last_total = session
+            .laps
+            .last()
+            .map_or(Duration::ZERO, |&(_, total)| total);
+        // Sessions saved before labels, timestamps or phases existed have none
+        let mut lap_labels = session.lap_labels;
+        lap_labels.resize(session.laps.len(), None);
+        let mut lap_timestamps = session.lap_timestamps;
+        lap_timestamps.resize(session.laps.len(), None);
+        let mut lap_phases = session.lap_phases;
+        lap_phases.resize(session.laps.len(), 0);
+        let start_lap = session.start_lap && !session.laps.is_empty();
+
+        Ok(Stopwatch {
+            name: session.name,
+            mode: session.mode,
+            start_time,
+            is_running: session.is_running,
+            laps: session.laps,
+            lap_labels,
+            lap_timestamps,
+            lap_phases,
+            phase: session.phase,
+            start_lap,
+            last_lap: start_time + last_total,
+            pause_time: if session.is_running { None } else { Some(now) },
+            paused: session.paused,
+            started: !session.unstarted,
+        })
+    }
+
+    pub fn add_lap(&mut self) {
+        let now = Instant::now();
+        let lap_time = now.duration_since(self.last_lap);
+        let total_time = now.duration_since(self.start_time);
+        self.laps.push((lap_time, total_time));
+        self.lap_labels.push(None);
+        self.lap_timestamps.push(Some(SystemTime::now()));
+        self.lap_phases.push(self.phase);
+        self.last_lap = now;
+    }
+
+    /// Records a zero-length lap at the very start, marking it as a lap boundary.
+    /// Meant for a stopwatch that hasn't counted anything yet; the lap in progress
+    /// still measures from the start. It measures nothing itself, so it's left out
+    /// of `timed_laps` and `lap_stats`.
+    pub fn add_start_lap(&mut self) {
+        self.start_lap = self.laps.is_empty();
+        self.laps.push((Duration::ZERO, Duration::ZERO));
+        self.lap_labels.push(None);
+        self.lap_timestamps.push(Some(SystemTime::now()));
+        self.lap_phases.push(self.phase);
+    }
+
+    /// Moves the clock forward by `offset`, as though it had been counting that
+    /// much longer: time carried over from elsewhere. The lap in progress grows by
+    /// it too, so later laps still add up to their totals. Works paused or running.
+    pub fn add_offset(&mut self, offset: Duration) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "offset is too large");
+        self.start_time = self.start_time.checked_sub(offset).ok_or_else(too_large)?;
+        self.last_lap = self.last_lap.checked_sub(offset).ok_or_else(too_large)?;
+        Ok(())
+    }
+
+    /// Removes the lap at `index` (oldest first). Totals are absolute so they stay
+    /// as recorded; the following lap absorbs the removed lap's time so every lap
+    /// time still equals the gap between consecutive totals.
+    pub fn delete_lap(&mut self, index: usize) -> Option<(Duration, Duration)> {
+        if index >= self.laps.len() {
+            return None;
+        }
+
+        let removed = self.laps.remove(index);
+        self.start_lap &= index != 0;
+        self.lap_labels.remove(index);
+        self.lap_timestamps.remove(index);
+        self.lap_phases.remove(index);
+        if let Some((next_lap, _)) = self.laps.get_mut(index) {
+            *next_lap += removed.0;
+        } else {
+            // Removing the newest lap hands its time back to the lap in progress
+            self.last_lap -= removed.0;
+        }
+        Some(removed)
+    }
+
+    /// Forgets every lap but leaves the clock alone, unlike `reset`. The lap in
+    /// progress carries on from the last lap that was recorded.
+    pub fn clear_laps(&mut self) {
+        self.laps.clear();
+        self.lap_labels.clear();
+        self.lap_timestamps.clear();
+        self.lap_phases.clear();
+        self.start_lap = false;
+    }
+
+    /// Whether the clock has ever run. Only a stopwatch from `new_paused` hasn't,
+    /// until `toggle_pause` first starts it.
+    pub fn has_started(&self) -> bool {
+        self.started
+    }
+
+    /// Pauses or resumes. Resuming starts a new phase; the first start of one
+    /// created with `new_paused` doesn't, whatever `add_offset` put on its clock.
+    pub fn toggle_pause(&mut self) {
+        if !self.is_running && self.started {
+            self.phase += 1;
+        }
+        self.is_running = !self.is_running;
+        if self.is_running {
+            // Shift both reference points forward by however long we were paused,
+            // so neither the total nor the in-progress lap counts the pause.
+            if let Some(paused_at) = self.pause_time.take() {
+                let paused_duration = Instant::now().duration_since(paused_at);
+                self.start_time += paused_duration;
+                self.last_lap += paused_duration;
+                // Waiting for the first start isn't a pause
+                if self.started {
+                    self.paused += paused_duration;
+                }
+            }
+            self.started = true;
+        } else {
+            self.pause_time = Some(Instant::now());
+        }
+    }
+
+    /// Pauses as though it happened when the clock read `elapsed`, so a limit that's
+    /// only checked on each tick still stops on the exact value. Does nothing if paused.
+    pub fn pause_at(&mut self, elapsed: Duration) {
+        if !self.is_running {
+            return;
+        }
+        self.is_running = false;
+        self.pause_time = Some((self.start_time + elapsed).min(Instant::now()));
+    }
+
+    /// Drops `gap` of running time as though the clock had been paused for it, e.g.
+    /// while the machine was suspended. Does nothing if paused.
+    pub fn skip(&mut self, gap: Duration) {
+        if !self.is_running {
+            return;
+        }
+        let now = Instant::now();
+        self.start_time = (self.start_time + gap).min(now);
+        self.last_lap = (self.last_lap + gap).min(now);
+    }
+
+    pub fn reset(&mut self) {
+        self.reset_clock();
+        self.laps.clear();
+        self.lap_labels.clear();
+        self.lap_timestamps.clear();
+        self.lap_phases.clear();
+        self.start_lap = false;
+        self.phase = 0;
+    }
+
+    /// Replaces every lap with `laps`, e.g. from `import_csv`, and sets the clock
+    /// to the last total so it carries on from there with a fresh lap in progress.
+    /// Labels and timestamps are dropped and the laps all go in one phase; running
+    /// or paused is kept.
+    pub fn replace_laps(&mut self, laps: Vec<(Duration, Duration)>) -> io::Result<()> {
+        let now = Instant::now();
+        let last_total = laps.last().map_or(Duration::ZERO, |&(_, total)| total);
+        self.start_time = now
+            .checked_sub(last_total)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "lap total is too large"))?;
+        self.last_lap = now;
+        if !self.is_running {
+            self.pause_time = Some(now);
+        }
+        self.lap_labels = vec![None; laps.len()];
+        self.lap_timestamps = vec![None; laps.len()];
+        self.lap_phases = vec![0; laps.len()];
+        self.start_lap = false;
+        self.phase = 0;
+        self.laps = laps;
+        Ok(())
+    }
+
+    /// Zeroes the clock and starts it running like `reset`, but keeps the laps.
+    /// Their totals stay as recorded, so they no longer line up with `elapsed`.
+    pub fn reset_clock(&mut self) {
+        let now = Instant::now();
+        self.start_time = now;
+        self.last_lap = now;
+        self.is_running = true;
+        self.pause_time = None;
+        self.paused = Duration::ZERO;
+        self.started = true;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.pause_time {
+            Some(paused_at) if !self.is_running => paused_at.duration_since(self.start_time),
+            _ => Instant::now().duration_since(self.start_time),
+        }
+    }
+
+    /// Total time spent paused since the stopwatch was created or last reset,
+    /// including the current pause. Waiting for one from `new_paused` to be
+    /// started doesn't count.
+    pub fn total_paused(&self) -> Duration {
+        match self.pause_time {
+            Some(paused_at) if !self.is_running && self.started => {
+                self.paused + paused_at.elapsed()
+            }
+            _ => self.paused,
+        }
+    }
+
+    /// Time shown on the main display: elapsed for a stopwatch, time left for a countdown.
+    pub fn display_time(&self) -> Duration {
+        match self.mode {
+            Mode::Stopwatch => self.elapsed(),
+            Mode::Countdown { target } => target.saturating_sub(self.elapsed()),
+        }
+    }
+
+    pub fn countdown_finished(&self) -> bool {
+        matches!(self.mode, Mode::Countdown { target } if self.elapsed() >= target)
+    }
+
+    /// Statistics over `timed_laps`, or `None` if there are none.
+    pub fn lap_stats(&self) -> Option<LapStats> {
+        let laps = self.timed_laps();
+        if laps.is_empty() {
+            return None;
+        }
+
+        let lap_times = laps.iter().map(|(lap_time, _)| *lap_time);
+        let count = laps.len();
+        let total: Duration = lap_times.clone().sum();
+        let avg = total.div_f64(count as f64);
+        let variance = lap_times
+            .clone()
+            .map(|lap| (lap.as_secs_f64() - avg.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        let std_dev = Duration::from_secs_f64(variance.sqrt());
+        Some(LapStats {
+            count,
+            total,
+            avg,
+            min: lap_times.clone().min()?,
+            max: lap_times.clone().max()?,
+            above_avg: lap_times.clone().filter(|&lap| lap > avg).count(),
+            below_avg: lap_times.filter(|&lap| lap < avg).count(),
+            std_dev,
+            cv: (count > 1 && !avg.is_zero()).then(|| std_dev.as_secs_f64() / avg.as_secs_f64()),
+        })
+    }
+}
+
+/// Personal bests across every session, kept in a small JSON file. Each is empty
+/// until a session with laps has been recorded.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    pub fastest_average: Option<Duration>,
+    pub most_laps: Option<usize>,
+}
+
+impl Records {
+    pub fn load(path: &Path) -> io::Result<Records> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json(path, self)
+    }
+
+    /// Counts `stopwatch`'s laps as a finished session, returning the records it
+    /// beat. Sessions without laps beat nothing.
+    pub fn update(&mut self, stopwatch: &Stopwatch) -> Vec<PersonalBest> {
+        let Some(stats) = stopwatch.lap_stats() else {
+            return Vec::new();
+        };
+        let mut beaten = Vec::new();
+        if self.fastest_average.is_none_or(|best| stats.avg < best) {
+            self.fastest_average = Some(stats.avg);
+            beaten.push(PersonalBest::Average);
+        }
+        if self.most_laps.is_none_or(|best| stats.count > best) {
+            self.most_laps = Some(stats.count);
+            beaten.push(PersonalBest::Laps);
+        }
+        beaten
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(value)?;
+    fs::write(path, json)
+}
+
+/// Formats `duration` as `12.34s`, `1m 5.00s`, `2h 0m 5.00s` or, from 24 hours
+/// on, `2d 2h 0m 5.00s`, with `precision` fractional digits on the seconds in
+/// every form.
+///
+/// The duration is rounded to the displayed precision before it's split into
+/// units, so `59.996s` at hundredths becomes `1m 0.00s` rather than `60.00s`.
+pub fn format_duration(duration: Duration, precision: Precision) -> String {
+    let digits = precision.digits();
+    let (total_seconds, fraction) = round_to_precision(duration, precision);
+    let (days, hours, minutes, seconds) = (
+        total_seconds / 86400,
+        total_seconds / 3600 % 24,
+        total_seconds / 60 % 60,
+        total_seconds % 60,
+    );
+    let seconds = format!("{}.{:0digits$}s", seconds, fraction, digits = digits);
+    if total_seconds < 60 {
+        seconds
+    } else if total_seconds < 3600 {
+        format!("{}m {}", minutes, seconds)
+    } else if total_seconds < 86400 {
+        format!("{}h {}m {}", hours, minutes, seconds)
+    } else {
+        format!("{}d {}h {}m {}", days, hours, minutes, seconds)
+    }
+}
+
+/// Formats `duration` as a zero-padded clock, `HH:MM:SS` plus `precision`
+/// fractional digits, e.g. `01:05:03.20`. Hours keep counting past 99 rather
+/// than rolling over into days.
+pub fn format_clock(duration: Duration, precision: Precision) -> String {
+    let digits = precision.digits();
+    let (total_seconds, fraction) = round_to_precision(duration, precision);
+    format!(
+        "{:02}:{:02}:{:02}.{:0digits$}",
+        total_seconds / 3600,
+        total_seconds / 60 % 60,
+        total_seconds % 60,
+        fraction,
+        digits = digits
+    )
+}
+
+/// Whole seconds and the fractional part in units of `precision`, rounded half up.
+fn round_to_precision(duration: Duration, precision: Precision) -> (u128, u128) {
+    let units_per_second = 10u128.pow(precision.digits() as u32);
+    let nanos_per_unit = 1_000_000_000 / units_per_second;
+    let units = (duration.as_nanos() + nanos_per_unit / 2) / nanos_per_unit;
+    (units / units_per_second, units % units_per_second)
+}
+
+/// Why `parse_duration` rejected its input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseDurationError {
+    Empty,
+    /// A number that doesn't parse, or a negative bare number of seconds.
+    InvalidNumber(String),
+    /// A unit with no number in front of it, like the `h` in `h30m`.
+    MissingNumber(String),
+    /// A number with no unit after it, like the `30` in `1h30`.
+    MissingUnit(String),
+    UnknownUnit(String),
+    /// Units repeated or not from largest to smallest, as in `30s1m`.
+    OutOfOrder(String),
+    UnexpectedChar(char),
+    TooLarge,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseDurationError::Empty => write!(f, "empty duration"),
+            ParseDurationError::InvalidNumber(number) => write!(f, "invalid number: {}", number),
+            ParseDurationError::MissingNumber(unit) => {
+                write!(f, "missing number before \"{}\"", unit)
+            }
+            ParseDurationError::MissingUnit(number) => {
+                write!(f, "missing unit after {} (expected h, m, s or ms)", number)
+            }
+            ParseDurationError::UnknownUnit(unit) => {
+                write!(f, "unknown unit \"{}\" (expected h, m, s or ms)", unit)
+            }
+            ParseDurationError::OutOfOrder(unit) => {
+                write!(f, "\"{}\" is repeated or out of order", unit)
+            }
+            ParseDurationError::UnexpectedChar(c) => write!(f, "unexpected character '{}'", c),
+            ParseDurationError::TooLarge => write!(f, "duration is too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+/// Parses a duration: bare seconds (`90`, `2.5`) or one or more number-unit
+/// pairs from largest unit to smallest, such as `90s`, `5m`, `1.5h`,
+/// `1h30m`, `1h 30m 15s` or `250ms`.
+pub fn parse_duration(s: &str) -> Result<Duration, ParseDurationError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(ParseDurationError::Empty);
+    }
+    if s.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+        && !s.contains(char::is_alphabetic)
+    {
+        let seconds: f64 = s
+            .parse()
+            .map_err(|_| ParseDurationError::InvalidNumber(s.to_string()))?;
+        if seconds < 0.0 {
+            return Err(ParseDurationError::InvalidNumber(s.to_string()));
+        }
+        return Duration::try_from_secs_f64(seconds).map_err(|_| ParseDurationError::TooLarge);
+    }
+
+    let mut seconds = 0.0;
+    let mut previous_rank = None;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        let after = after.trim_start();
+        let unit_end = after
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(unit_end);
+
+        match (number.is_empty(), unit.is_empty()) {
+            (true, true) => {
+                let c = after.chars().next().unwrap_or_default();
+                return Err(ParseDurationError::UnexpectedChar(c));
+            }
+            (true, false) => return Err(ParseDurationError::MissingNumber(unit.to_string())),
+            (false, true) => return Err(ParseDurationError::MissingUnit(number.to_string())),
+            (false, false) => {}
+        }
+
+        // Rank orders the units from largest to smallest
+        let (rank, unit_seconds) = match unit {
+            "h" => (0, 3600.0),
+            "m" => (1, 60.0),
+            "s" => (2, 1.0),
+            "ms" => (3, 0.001),
+            _ => return Err(ParseDurationError::UnknownUnit(unit.to_string())),
+        };
+        if previous_rank.is_some_and(|previous| previous >= rank) {
+            return Err(ParseDurationError::OutOfOrder(unit.to_string()));
+        }
+        previous_rank = Some(rank);
+
+        let value: f64 = number
+            .parse()
+            .map_err(|_| ParseDurationError::InvalidNumber(number.to_string()))?;
+        seconds += value * unit_seconds;
+        rest = after.trim_start();
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| ParseDurationError::TooLarge)
+}
+
+/// JSON form of a stopwatch for `export_json`, with durations in seconds.
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    name: Option<&'a str>,
+    elapsed: f64,
+    laps: Vec<JsonLap<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonLap<'a> {
+    lap: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    lap_time: f64,
+    total_time: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<String>,
+}
+
+/// Writes the elapsed time and laps (oldest first) as a JSON object, with every
+/// duration as float seconds and each lap's wall-clock `timestamp` in ISO 8601.
+pub fn export_json(stopwatch: &Stopwatch, writer: &mut impl Write) -> io::Result<()> {
+    let export = JsonExport {
+        name: stopwatch.name(),
+        elapsed: stopwatch.elapsed().as_secs_f64(),
+        laps: stopwatch
+            .laps()
+            .iter()
+            .enumerate()
+            .map(|(i, (lap_time, total_time))| JsonLap {
+                lap: i + 1,
+                label: stopwatch.lap_label(i),
+                lap_time: lap_time.as_secs_f64(),
+                total_time: total_time.as_secs_f64(),
+                timestamp: stopwatch.lap_timestamp(i).map(format_timestamp),
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *writer, &export)?;
+    writeln!(writer)
+}
+
+/// Writes the laps as CSV: a `lap,lap_time_secs,total_time_secs,label,timestamp`
+/// header followed by one row per lap, oldest first, with the wall-clock time
+/// in ISO 8601 and an empty field where there's no label or timestamp. A named
+/// session is preceded by a `# session: <name>` comment line.
+pub fn export_csv(stopwatch: &Stopwatch, writer: &mut impl Write) -> io::Result<()> {
+    if let Some(name) = stopwatch.name() {
+        writeln!(writer, "# session: {}", name)?;
+    }
+    writeln!(writer, "lap,lap_time_secs,total_time_secs,label,timestamp")?;
+    for (i, (lap_time, total_time)) in stopwatch.laps().iter().enumerate() {
+        writeln!(
+            writer,
+            "{},{:.3},{:.3},{},{}",
+            i + 1,
+            lap_time.as_secs_f64(),
+            total_time.as_secs_f64(),
+            csv_field(stopwatch.lap_label(i).unwrap_or_default()),
+            stopwatch
+                .lap_timestamp(i)
+                .map(format_timestamp)
+                .unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
+
+/// Reads laps back from a file written by `export_csv`, as `(lap_time,
+/// total_time)` oldest first. `#` comment lines and blank lines are skipped, and
+/// only the first three columns are used, so files from before the label and
+/// timestamp columns existed load too. Laps must be numbered from 1 with totals
+/// that never go backwards.
+pub fn import_csv(reader: &mut impl Read) -> io::Result<Vec<(Duration, Duration)>> {
+    let invalid = |line: usize, message: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {}: {}", line, message),
+        )
+    };
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'));
+
+    match lines.next() {
+        Some((_, header)) if header.starts_with("lap,lap_time_secs,total_time_secs") => {}
+        Some((line, header)) => {
+            return Err(invalid(
+                line,
+                format!("not a laps export header: {}", header),
+            ))
+        }
+        None => return Err(invalid(1, "no header".to_string())),
+    }
+
+    let mut laps: Vec<(Duration, Duration)> = Vec::new();
+    for (line, row) in lines {
+        let fields: Vec<&str> = row.splitn(4, ',').collect();
+        if fields.len() < 3 {
+            return Err(invalid(
+                line,
+                format!("expected at least 3 columns: {}", row),
+            ));
+        }
+        if fields[0].trim().parse() != Ok(laps.len() + 1) {
+            return Err(invalid(
+                line,
+                format!("expected lap {}, got {:?}", laps.len() + 1, fields[0]),
+            ));
+        }
+        let seconds = |field: &str| {
+            field
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| invalid(line, format!("invalid number of seconds: {:?}", field)))
+        };
+        let lap_time = seconds(fields[1])?;
+        let total_time = seconds(fields[2])?;
+        if laps
+            .last()
+            .is_some_and(|&(_, previous)| total_time < previous)
+        {
+            return Err(invalid(line, "total time goes backwards".to_string()));
+        }
+        laps.push((lap_time, total_time));
+    }
+    Ok(laps)
+}
+
+/// Writes the laps as a GitHub-flavored Markdown table, oldest first, with
+/// durations formatted at `precision` and the wall-clock time each lap was
+/// recorded. The Label column is only included when at least one lap has a
+/// label. With no laps, only the header is written.
+pub fn export_markdown(
+    stopwatch: &Stopwatch,
+    precision: Precision,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let laps = stopwatch.laps();
+    let labelled = (0..laps.len()).any(|i| stopwatch.lap_label(i).is_some());
+
+    if labelled {
+        writeln!(writer, "| Lap | Lap Time | Delta | Total | Time | Label |")?;
+        writeln!(writer, "|----:|---------:|------:|------:|:-----|:------|")?;
+    } else {
+        writeln!(writer, "| Lap | Lap Time | Delta | Total | Time |")?;
+        writeln!(writer, "|----:|---------:|------:|------:|:-----|")?;
+    }
+    for (i, &(lap_time, total_time)) in laps.iter().enumerate() {
+        let delta = match i.checked_sub(1).map(|prev| laps[prev].0) {
+            Some(previous) if lap_time >= previous => {
+                format!("+{}", format_duration(lap_time - previous, precision))
+            }
+            Some(previous) => format!("-{}", format_duration(previous - lap_time, precision)),
+            None => String::new(),
+        };
+        write!(
+            writer,
+            "| {} | {} | {} | {} | {} |",
+            i + 1,
+            format_duration(lap_time, precision),
+            delta,
+            format_duration(total_time, precision),
+            stopwatch
+                .lap_timestamp(i)
+                .map(format_timestamp)
+                .unwrap_or_default()
+        )?;
+        if labelled {
+            // A bare pipe would end the cell early
+            let label = stopwatch.lap_label(i).unwrap_or_default();
+            write!(writer, " {} |", label.replace('|', "\\|"))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// ISO 8601 form of a lap timestamp in local time, to the millisecond, e.g.
+/// `2024-05-01T14:03:27.512+02:00`.
+fn format_timestamp(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+/// Quotes `field` if it contains anything CSV treats specially.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn elapsed_keeps_counting_after_a_lap_until_paused() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        stopwatch.add_lap();
+        let at_lap = stopwatch.laps()[0].1;
+        thread::sleep(Duration::from_millis(30));
+        let before_pause = stopwatch.elapsed();
+        stopwatch.toggle_pause();
+        let paused = stopwatch.elapsed();
+        assert!(before_pause >= at_lap + Duration::from_millis(30));
+        assert!(paused >= before_pause);
+
+        // Paused, the clock holds still instead of jumping back to the lap
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(stopwatch.elapsed(), paused);
+    }
+
+    #[test]
+    fn resuming_leaves_the_pause_out_of_elapsed() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        thread::sleep(Duration::from_millis(50));
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(200));
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(50));
+
+        // 100ms of running; counting the pause would make it at least 300ms
+        let elapsed = stopwatch.elapsed();
+        assert!(elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(250), "{:?}", elapsed);
+    }
+
+    #[test]
+    fn laps_after_a_resume_leave_the_pause_out() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        stopwatch.add_lap();
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(200));
+        stopwatch.toggle_pause();
+        stopwatch.add_lap();
+
+        let (lap_time, total) = stopwatch.laps()[1];
+        assert!(lap_time < Duration::from_millis(100), "{:?}", lap_time);
+        assert!(total < Duration::from_millis(100), "{:?}", total);
+    }
+
+    /// A file in the temp directory for this test run alone.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("stopwatch-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn save_and_load_round_trip_a_paused_stopwatch() {
+        let mut stopwatch = Stopwatch::new(Mode::Countdown {
+            target: Duration::from_secs(300),
+        });
+        stopwatch.set_name("intervals");
+        stopwatch.add_lap();
+        stopwatch.add_lap();
+        stopwatch.set_lap_label(1, "warmup");
+        stopwatch.toggle_pause();
+
+        let path = temp_path("session.json");
+        stopwatch.save(&path).unwrap();
+        let loaded = Stopwatch::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.name(), Some("intervals"));
+        assert_eq!(loaded.mode(), stopwatch.mode());
+        assert_eq!(loaded.laps(), stopwatch.laps());
+        assert_eq!(loaded.lap_label(0), None);
+        assert_eq!(loaded.lap_label(1), Some("warmup"));
+        assert_eq!(loaded.lap_timestamp(0), stopwatch.lap_timestamp(0));
+        assert!(!loaded.is_running());
+        assert_eq!(loaded.elapsed(), stopwatch.elapsed());
+    }
+
+    #[test]
+    fn a_running_stopwatch_keeps_running_after_loading() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        thread::sleep(Duration::from_millis(20));
+        stopwatch.add_lap();
+
+        let path = temp_path("running.json");
+        Stopwatch::save_all(&[stopwatch.clone(), stopwatch], &path).unwrap();
+        let loaded = Stopwatch::load_all(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded[0].is_running());
+        assert!(loaded[0].elapsed() >= loaded[0].laps()[0].1);
+    }
+
+    /// A paused stopwatch with laps of `millis` each, so its clock reads their sum.
+    fn with_laps(millis: &[u64]) -> Stopwatch {
+        let mut total = Duration::ZERO;
+        let laps = millis
+            .iter()
+            .map(|&ms| {
+                total += Duration::from_millis(ms);
+                (Duration::from_millis(ms), total)
+            })
+            .collect();
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.replace_laps(laps).unwrap();
+        stopwatch
+    }
+
+    #[test]
+    fn lap_stats_summarise_the_laps() {
+        let stats = with_laps(&[3000, 1000, 2000, 6000]).lap_stats().unwrap();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.total, Duration::from_secs(12));
+        assert_eq!(stats.avg, Duration::from_secs(3));
+        assert_eq!(stats.min, Duration::from_secs(1));
+        assert_eq!(stats.max, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn no_lap_stats_without_laps() {
+        assert_eq!(Stopwatch::new(Mode::Stopwatch).lap_stats(), None);
+    }
+
+    #[test]
+    fn format_duration_shows_the_chosen_precision() {
+        let duration = Duration::from_micros(5_123_456);
+        assert_eq!(format_duration(duration, Precision::Tenths), "5.1s");
+        assert_eq!(format_duration(duration, Precision::Hundredths), "5.12s");
+        assert_eq!(format_duration(duration, Precision::Milliseconds), "5.123s");
+
+        let duration = Duration::from_millis(65_040);
+        assert_eq!(format_duration(duration, Precision::Tenths), "1m 5.0s");
+        assert_eq!(format_duration(duration, Precision::Hundredths), "1m 5.04s");
+        assert_eq!(
+            format_duration(duration, Precision::Milliseconds),
+            "1m 5.040s"
+        );
+    }
+
+    #[test]
+    fn precision_parses_its_names() {
+        assert_eq!(Precision::parse("tenths"), Some(Precision::Tenths));
+        assert_eq!(Precision::parse("hundredths"), Some(Precision::Hundredths));
+        assert_eq!(Precision::parse("ms"), Some(Precision::Milliseconds));
+        assert_eq!(Precision::parse("seconds"), None);
+    }
+
+    #[test]
+    fn format_duration_rounds_before_picking_units() {
+        let hundredths =
+            |micros| format_duration(Duration::from_micros(micros), Precision::Hundredths);
+        assert_eq!(hundredths(0), "0.00s");
+        assert_eq!(hundredths(4_999), "0.00s");
+        assert_eq!(hundredths(5_000), "0.01s");
+        assert_eq!(hundredths(999_999), "1.00s");
+        assert_eq!(hundredths(59_994_000), "59.99s");
+        assert_eq!(hundredths(59_996_000), "1m 0.00s");
+        assert_eq!(hundredths(60_000_000), "1m 0.00s");
+        assert_eq!(hundredths(3_599_996_000), "1h 0m 0.00s");
+        assert_eq!(hundredths(3_600_000_000), "1h 0m 0.00s");
+        assert_eq!(hundredths(3_661_500_000), "1h 1m 1.50s");
+    }
+
+    #[test]
+    fn format_duration_keeps_the_precision_in_every_form() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(format_duration(hour, Precision::Tenths), "1h 0m 0.0s");
+        assert_eq!(
+            format_duration(hour, Precision::Milliseconds),
+            "1h 0m 0.000s"
+        );
+        let almost = Duration::from_micros(59_999_600);
+        assert_eq!(
+            format_duration(almost, Precision::Milliseconds),
+            "1m 0.000s"
+        );
+        assert_eq!(format_duration(almost, Precision::Tenths), "1m 0.0s");
+    }
+
+    #[test]
+    fn parse_duration_reads_bare_seconds() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2.5"), Ok(Duration::from_millis(2500)));
+        assert_eq!(parse_duration(" 0 "), Ok(Duration::ZERO));
+        assert_eq!(parse_duration(".5"), Ok(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn parse_duration_reads_units() {
+        let secs = Duration::from_secs;
+        assert_eq!(parse_duration("90s"), Ok(secs(90)));
+        assert_eq!(parse_duration("5m"), Ok(secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(secs(3600)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5h"), Ok(secs(5400)));
+        assert_eq!(parse_duration("1h30m"), Ok(secs(5400)));
+        assert_eq!(parse_duration("1h30m15s"), Ok(secs(5415)));
+        assert_eq!(parse_duration("1h 30m 15s"), Ok(secs(5415)));
+        assert_eq!(parse_duration("1 h 30 m"), Ok(secs(5400)));
+        assert_eq!(
+            parse_duration("2m500ms"),
+            Ok(Duration::from_millis(120_500))
+        );
+        assert_eq!(parse_duration("1h15s"), Ok(secs(3615)));
+        assert_eq!(parse_duration("0s"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        use ParseDurationError::*;
+        assert_eq!(parse_duration(""), Err(Empty));
+        assert_eq!(parse_duration("   "), Err(Empty));
+        assert_eq!(parse_duration("-5"), Err(InvalidNumber("-5".to_string())));
+        assert_eq!(
+            parse_duration("1.2.3"),
+            Err(InvalidNumber("1.2.3".to_string()))
+        );
+        assert_eq!(
+            parse_duration("1.2.3s"),
+            Err(InvalidNumber("1.2.3".to_string()))
+        );
+        assert_eq!(parse_duration("h30m"), Err(MissingNumber("h".to_string())));
+        assert_eq!(parse_duration("1h30"), Err(MissingUnit("30".to_string())));
+        assert_eq!(parse_duration("5x"), Err(UnknownUnit("x".to_string())));
+        assert_eq!(parse_duration("5min"), Err(UnknownUnit("min".to_string())));
+        assert_eq!(parse_duration("30s1m"), Err(OutOfOrder("m".to_string())));
+        assert_eq!(parse_duration("1m1m"), Err(OutOfOrder("m".to_string())));
+        assert_eq!(parse_duration("5m!"), Err(UnexpectedChar('!')));
+        assert_eq!(parse_duration("-5m"), Err(UnexpectedChar('-')));
+        assert_eq!(parse_duration("1e30"), Err(UnknownUnit("e".to_string())));
+        assert_eq!(parse_duration("100000000000000000000000h"), Err(TooLarge));
+    }
+
+    #[test]
+    fn parse_duration_errors_read_well() {
+        let message = |s| parse_duration(s).unwrap_err().to_string();
+        assert_eq!(message(""), "empty duration");
+        assert_eq!(
+            message("1h30"),
+            "missing unit after 30 (expected h, m, s or ms)"
+        );
+        assert_eq!(message("30s1m"), "\"m\" is repeated or out of order");
+    }
+
+    #[test]
+    fn lap_stats_count_laps_either_side_of_the_average() {
+        // Average 3s: two slower, one faster, one right on it
+        let stats = with_laps(&[3000, 4000, 1000, 4000]).lap_stats().unwrap();
+        assert_eq!(stats.avg, Duration::from_secs(3));
+        assert_eq!(stats.above_avg, 2);
+        assert_eq!(stats.below_avg, 1);
+
+        let stats = with_laps(&[2000, 2000]).lap_stats().unwrap();
+        assert_eq!((stats.above_avg, stats.below_avg), (0, 0));
+    }
+
+    #[test]
+    fn total_paused_adds_up_every_pause() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        for _ in 0..3 {
+            stopwatch.toggle_pause();
+            thread::sleep(Duration::from_millis(40));
+            stopwatch.toggle_pause();
+            thread::sleep(Duration::from_millis(10));
+        }
+        let paused = stopwatch.total_paused();
+        assert!(paused >= Duration::from_millis(120), "{:?}", paused);
+        assert!(paused < Duration::from_millis(200), "{:?}", paused);
+
+        // The pause in progress counts too, and a running clock stops adding to it
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(40));
+        assert!(stopwatch.total_paused() >= paused + Duration::from_millis(40));
+        stopwatch.toggle_pause();
+        let resumed = stopwatch.total_paused();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(stopwatch.total_paused(), resumed);
+
+        stopwatch.reset();
+        assert_eq!(stopwatch.total_paused(), Duration::ZERO);
+    }
+
+    #[test]
+    fn waiting_for_the_first_start_isnt_paused_time() {
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(stopwatch.total_paused(), Duration::ZERO);
+        stopwatch.toggle_pause();
+        assert_eq!(stopwatch.total_paused(), Duration::ZERO);
+        assert!(stopwatch.elapsed() < Duration::from_millis(50));
+
+        // Later pauses count as usual
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(30));
+        stopwatch.toggle_pause();
+        assert!(stopwatch.total_paused() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn an_unstarted_stopwatch_stays_unstarted_through_a_save() {
+        let path = temp_path("unstarted.json");
+        Stopwatch::new_paused(Mode::Stopwatch).save(&path).unwrap();
+        let mut loaded = Stopwatch::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!loaded.has_started());
+        thread::sleep(Duration::from_millis(20));
+        loaded.toggle_pause();
+        assert_eq!(loaded.total_paused(), Duration::ZERO);
+    }
+
+    #[test]
+    fn format_duration_counts_days_from_24_hours() {
+        let hours =
+            |hours: u64| format_duration(Duration::from_secs(hours * 3600), Precision::Tenths);
+        assert_eq!(hours(23), "23h 0m 0.0s");
+        assert_eq!(hours(24), "1d 0h 0m 0.0s");
+        assert_eq!(hours(25), "1d 1h 0m 0.0s");
+        assert_eq!(hours(50), "2d 2h 0m 0.0s");
+        let long = Duration::from_secs(10 * 86400 + 3 * 3600 + 4 * 60 + 5);
+        assert_eq!(
+            format_duration(long, Precision::Hundredths),
+            "10d 3h 4m 5.00s"
+        );
+    }
+
+    #[test]
+    fn format_clock_zero_pads_every_field() {
+        let clock = |millis| format_clock(Duration::from_millis(millis), Precision::Hundredths);
+        assert_eq!(clock(0), "00:00:00.00");
+        assert_eq!(clock(5_070), "00:00:05.07");
+        assert_eq!(clock(65_200), "00:01:05.20");
+        assert_eq!(clock(3_903_200), "01:05:03.20");
+        assert_eq!(
+            format_clock(Duration::from_millis(5_070), Precision::Milliseconds),
+            "00:00:05.070"
+        );
+    }
+
+    #[test]
+    fn format_clock_hours_run_past_99() {
+        let hours = |hours: u64| format_clock(Duration::from_secs(hours * 3600), Precision::Tenths);
+        assert_eq!(hours(99), "99:00:00.0");
+        assert_eq!(hours(100), "100:00:00.0");
+        assert_eq!(hours(1234), "1234:00:00.0");
+    }
+
+    #[test]
+    fn display_format_picks_words_or_clock() {
+        let duration = Duration::from_millis(65_200);
+        assert_eq!(
+            DisplayFormat::Words.format(duration, Precision::Tenths),
+            "1m 5.2s"
+        );
+        assert_eq!(
+            DisplayFormat::Clock.format(duration, Precision::Tenths),
+            "00:01:05.2"
+        );
+    }
+
+    #[test]
+    fn lap_stats_measure_the_spread() {
+        // The textbook set: average 5s, population standard deviation 2s
+        let stats = with_laps(&[2000, 4000, 4000, 4000, 5000, 5000, 7000, 9000])
+            .lap_stats()
+            .unwrap();
+        assert_eq!(stats.avg, Duration::from_secs(5));
+        assert!(
+            (stats.std_dev.as_secs_f64() - 2.0).abs() < 1e-6,
+            "{:?}",
+            stats.std_dev
+        );
+        assert!((stats.cv.unwrap() - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_single_lap_has_no_spread() {
+        let stats = with_laps(&[3000]).lap_stats().unwrap();
+        assert_eq!(stats.std_dev, Duration::ZERO);
+        assert_eq!(stats.cv, None);
+
+        let stats = with_laps(&[0, 0]).lap_stats().unwrap();
+        assert_eq!(stats.cv, None);
+    }
+
+    #[test]
+    fn the_first_lap_after_a_start_lap_measures_from_the_start() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        stopwatch.add_start_lap();
+        thread::sleep(Duration::from_millis(20));
+        stopwatch.add_lap();
+
+        let laps = stopwatch.laps();
+        assert_eq!(laps[0], (Duration::ZERO, Duration::ZERO));
+        assert_eq!(laps[1].0, laps[1].1);
+        assert!(laps[1].0 >= Duration::from_millis(20));
+        assert_eq!(stopwatch.timed_laps(), &laps[1..]);
+    }
+
+    #[test]
+    fn a_start_lap_is_left_out_of_the_stats_and_records() {
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.add_start_lap();
+        assert_eq!(stopwatch.lap_stats(), None);
+        let mut records = Records::default();
+        assert!(records.update(&stopwatch).is_empty());
+        assert_eq!(records.fastest_average, None);
+
+        stopwatch.toggle_pause();
+        thread::sleep(Duration::from_millis(20));
+        stopwatch.add_lap();
+        let stats = stopwatch.lap_stats().unwrap();
+        assert_eq!(stats.count, 1);
+        assert_eq!(stats.min, stopwatch.laps()[1].0);
+    }
+
+    #[test]
+    fn the_start_lap_mark_follows_the_laps() {
+        let mut stopwatch = with_laps(&[]);
+        stopwatch.add_start_lap();
+        stopwatch.add_lap();
+
+        let path = temp_path("start-lap.json");
+        stopwatch.save(&path).unwrap();
+        let loaded = Stopwatch::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(loaded.has_start_lap());
+
+        // Deleting a later lap keeps it; deleting the start lap itself doesn't
+        let mut deleted = stopwatch.clone();
+        deleted.delete_lap(1);
+        assert!(deleted.has_start_lap());
+        stopwatch.delete_lap(0);
+        assert!(!stopwatch.has_start_lap());
+        assert_eq!(stopwatch.timed_laps().len(), 1);
+
+        // A start lap only goes in first
+        stopwatch.add_start_lap();
+        assert!(!stopwatch.has_start_lap());
+    }
+
+    #[test]
+    fn an_offset_starts_the_clock_there() {
+        let offset = Duration::from_secs(600);
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        stopwatch.add_offset(offset).unwrap();
+        let elapsed = stopwatch.elapsed();
+        assert!(elapsed >= offset);
+        assert!(
+            elapsed < offset + Duration::from_millis(50),
+            "{:?}",
+            elapsed
+        );
+
+        // The first lap and every total include it
+        stopwatch.add_lap();
+        stopwatch.add_lap();
+        let laps = stopwatch.laps();
+        assert!(laps[0].0 >= offset && laps[0].1 >= offset);
+        assert!(laps[1].0 < Duration::from_millis(50));
+        assert_eq!(laps[1].1, laps[0].1 + laps[1].0);
+    }
+
+    #[test]
+    fn a_paused_offset_waits_to_be_started() {
+        let offset = Duration::from_secs(600);
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.add_offset(offset).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(stopwatch.elapsed(), offset);
+        assert!(!stopwatch.has_started());
+
+        // Starting it is a start, not a resume
+        stopwatch.toggle_pause();
+        stopwatch.add_lap();
+        assert_eq!(stopwatch.lap_phase(0), 0);
+        assert_eq!(stopwatch.total_paused(), Duration::ZERO);
+        assert!(stopwatch.elapsed() < offset + Duration::from_millis(50));
+    }
+
+    #[test]
+    fn an_offset_too_large_for_the_clock_is_an_error() {
+        let mut stopwatch = Stopwatch::new(Mode::Stopwatch);
+        let err = stopwatch.add_offset(Duration::MAX).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -1,90 +1,659 @@
+mod big_digits;
+mod config;
+mod keys;
+
+use arboard::Clipboard;
+use big_digits::big_digits;
+use chrono::{DateTime, Local, TimeDelta};
+use config::{Config, Palette, Theme};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    cursor::{MoveTo, Show},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEventKind,
+    },
     execute,
+    style::Print,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use keys::{Action, KeyBindings};
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout},
+    backend::{Backend, CrosstermBackend, TestBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
-    Frame, Terminal,
+    widgets::{
+        Block, Borders, Clear, Gauge, HighlightSpacing, LineGauge, List, ListItem, ListState,
+        Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Sparkline, Tabs,
+    },
+    Frame, Terminal, TerminalOptions, Viewport,
+};
+use serde::{de::IntoDeserializer, Deserialize};
+#[cfg(unix)]
+use signal_hook::{consts::SIGTSTP, low_level::emulate_default_handler};
+#[cfg(unix)]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
 };
 use std::{
-    io,
-    time::{Duration, Instant},
+    cmp::Reverse,
+    collections::VecDeque,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write},
+    panic,
+    path::{Path, PathBuf},
+    process,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use stopwatch::{
+    export_csv, export_json, export_markdown, format_duration, import_csv, parse_duration,
+    DisplayFormat, Interval, Mode, PersonalBest, Phase, Pomodoro, Precision, Records, Stopwatch,
+};
+
+/// How long a status message stays on screen before the controls come back.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Redraw interval while the clock is ticking, fast enough for a smooth display.
+/// Overridden by `--tick-rate` or `timer.tick_rate` in the config.
+const TICK_RATE: Duration = Duration::from_millis(50);
+
+/// Bounds for a user-supplied tick rate. Faster than this just burns CPU;
+/// slower and the clock visibly jumps.
+const MIN_TICK_RATE: Duration = Duration::from_millis(10);
+const MAX_TICK_RATE: Duration = Duration::from_secs(1);
+
+/// Roughly one frame at 60Hz, so the last displayed digit doesn't stutter
+/// when hundredths or milliseconds are explicitly requested.
+const SMOOTH_TICK_RATE: Duration = Duration::from_millis(16);
+
+/// Longest a running timer goes without a redraw when nothing its frame key
+/// covers has changed, so slower-moving figures like the lap rate catch up.
+const MAX_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event poll interval while paused. Nothing on screen changes on its own,
+/// so we only wake up occasionally to expire status messages.
+const IDLE_POLL_RATE: Duration = Duration::from_millis(250);
+
+/// How long the timer border flashes after the alarm goes off.
+const ALARM_FLASH_DURATION: Duration = Duration::from_secs(3);
+
+/// How long the timer is highlighted after passing a milestone.
+const MILESTONE_FLASH_DURATION: Duration = Duration::from_secs(1);
+
+/// Time-of-day format for the start and current time in the title bar.
+const CLOCK_FORMAT: &str = "%H:%M:%S";
+
+/// Timestamp format for lines in the `--log` file.
+const LOG_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Destructive actions remembered per timer for undo.
+const UNDO_LIMIT: usize = 10;
+
+/// How many laps PageUp/PageDown move the selection by default.
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// How much `]` and `[` add to or take off a countdown's target.
+const COUNTDOWN_STEP: Duration = Duration::from_secs(30);
+
+/// Blank space kept around the whole UI.
+const MARGIN: u16 = 2;
+
+/// Smallest laps list worth showing: a border, the column headings and a single row.
+const MIN_LAPS_HEIGHT: u16 = 4;
+
+/// Rows the UI takes up below the prompt with `--inline`.
+const INLINE_HEIGHT: u16 = 20;
+
+/// Rows taken by the lap time sparkline, border included.
+const GRAPH_HEIGHT: u16 = 5;
+
+/// Drawn beside the selected lap by themes that mark it, such as `high-contrast`.
+const SELECTION_MARKER: &str = "> ";
+
+/// Spacing between columns in the laps table.
+const COLUMN_GAP: &str = "   ";
+
+/// What each key does, as listed in the help overlay. The keys themselves come
+/// from the bindings, so remapped keys are shown correctly.
+const KEY_HELP: &[(&[Action], &str)] = &[
+    (&[Action::Lap], "Record a lap"),
+    (&[Action::Pause], "Pause / resume"),
+    (&[Action::Reset], "Reset the stopwatch (press twice)"),
+    (&[Action::ResetClock], "Zero the clock, keeping the laps"),
+    (&[Action::DeleteLap], "Delete the selected lap"),
+    (
+        &[Action::ClearLaps],
+        "Clear all laps, keep the clock running",
+    ),
+    (&[Action::Undo], "Undo the last reset, deletion or clear"),
+    (&[Action::Export], "Export laps to CSV"),
+    (&[Action::ExportMarkdown], "Export laps as a Markdown table"),
+    (&[Action::Import], "Import laps from an exported CSV"),
+    (&[Action::CopyLap], "Copy selected lap to the clipboard"),
+    (&[Action::CopyAllLaps], "Copy all laps to the clipboard"),
+    (
+        &[Action::CopyElapsed],
+        "Copy the elapsed time to the clipboard",
+    ),
+    (&[Action::CopyCsv], "Copy all laps to the clipboard as CSV"),
+    (&[Action::ToggleStats], "Show / hide statistics"),
+    (&[Action::ToggleGraph], "Show / hide lap time graph"),
+    (&[Action::ToggleBigDigits], "Toggle big digits"),
+    (&[Action::ToggleMinimal], "Show only the time"),
+    (
+        &[Action::Freeze],
+        "Freeze / unfreeze the shown time, the clock keeps running",
+    ),
+    (
+        &[Action::ToggleFormat],
+        "Switch between 1m 5.00s and 00:01:05.00",
+    ),
+    (&[Action::CycleTheme], "Switch to the next color theme"),
+    (&[Action::ToggleBell], "Toggle the bell on each lap"),
+    (&[Action::ToggleFollow], "Toggle selecting each new lap"),
+    (&[Action::SetAlarm], "Set or clear an alarm"),
+    (
+        &[Action::ExtendCountdown, Action::ShortenCountdown],
+        "Add / take 30s off the countdown",
+    ),
+    (&[Action::NameSession], "Name the session"),
+    (&[Action::LabelLap], "Label the selected lap"),
+    (&[Action::ScrollUp], "Select previous lap"),
+    (&[Action::ScrollDown], "Select next lap"),
+    (
+        &[Action::PageUp, Action::PageDown],
+        "Move the selection a page",
+    ),
+    (&[Action::NewestLap], "Select newest lap"),
+    (&[Action::OldestLap], "Select oldest lap"),
+    (
+        &[Action::FastestLap, Action::SlowestLap],
+        "Select fastest / slowest lap",
+    ),
+    (
+        &[Action::SortLaps],
+        "Sort laps: as recorded, fastest or slowest first",
+    ),
+    (
+        &[Action::ReferenceLap],
+        "Compare laps against the selected one",
+    ),
+    (
+        &[Action::NextTimer, Action::PreviousTimer],
+        "Next / previous timer",
+    ),
+    (
+        &[Action::AddTimer, Action::RemoveTimer],
+        "Add / remove a timer",
+    ),
+    (&[Action::Command], "Type a command, e.g. countdown 5m"),
+    (&[Action::Help], "Show / hide this help"),
+    (&[Action::Quit], "Quit"),
+];
+
+/// Help entries that aren't remappable.
+const FIXED_KEY_HELP: &[(&str, &str)] = &[
+    ("Mouse", "Scroll or click to select a lap"),
+    ("CTRL+C", "Quit from anywhere"),
+    ("CTRL+L", "Redraw the screen"),
+    #[cfg(unix)]
+    ("CTRL+Z", "Suspend to the shell; fg to come back"),
+];
+
+/// Whether keys drive the stopwatch or are typed into a prompt, and if so which one.
+#[derive(Clone, Copy, PartialEq)]
+enum InputMode {
+    Normal,
+    Alarm,
+    SessionName,
+    LapLabel,
+    ImportPath,
+    Command,
+}
+
+impl InputMode {
+    /// Text shown before the input buffer, or `None` when no prompt is open.
+    fn prompt(self) -> Option<&'static str> {
+        match self {
+            InputMode::Normal => None,
+            InputMode::Alarm => Some("Alarm at (e.g. 5m, empty to clear): "),
+            InputMode::SessionName => Some("Session name (empty to clear): "),
+            InputMode::LapLabel => Some("Lap label (empty to clear): "),
+            InputMode::ImportPath => Some("Import laps from CSV: "),
+            InputMode::Command => Some(":"),
+        }
+    }
+}
+
+/// One stopwatch tab: the timing core plus the on-screen state that belongs to it alone.
+struct TimerState {
+    stopwatch: Stopwatch,
+    countdown_alerted: bool,
+    alarm: Option<Duration>,
+    alarm_fired_at: Option<Instant>, // Set while elapsed is past the alarm
+    auto_stopped_at: Option<Instant>, // Set once `--auto-stop` has paused this timer
+    // Wall-clock time the timer started, kept apart from the `Instant`s in the core.
+    // Pauses aren't subtracted, so a resumed timer just reports now minus elapsed.
+    started_at: DateTime<Local>,
+    last_milestone: Option<u32>, // Milestones passed so far; `None` until first checked
+    milestone_at: Option<Instant>, // When the most recent milestone was passed
+    pomodoro: Option<Pomodoro>,  // Work/break cycle driving the countdown, if any
+    interval: Option<Interval>,  // Repeating rounds driving the countdown, if any
+    frozen: Option<Duration>,    // Time held on screen while the clock runs on underneath
+    laps_list_state: ListState,  // Selected row, in `display_order`
+    lap_sort: LapSort,
+    reference_lap: Option<usize>, // Lap every other one is compared against, oldest first
+    resume_lap_count: usize,      // Laps recorded before the timer was last resumed
+    history: Vec<Snapshot>,       // Most recent last, at most `UNDO_LIMIT`
+    dirty: bool,                  // Laps were recorded since the last export
+}
 
+/// Order of the laps list. Lap numbers always follow the order laps were recorded.
+#[derive(Clone, Copy, PartialEq)]
+enum LapSort {
+    Chronological, // Newest first
+    Fastest,       // By lap time, ascending
+    Slowest,       // By lap time, descending
+}
+
+impl LapSort {
+    fn next(self) -> LapSort {
+        match self {
+            LapSort::Chronological => LapSort::Fastest,
+            LapSort::Fastest => LapSort::Slowest,
+            LapSort::Slowest => LapSort::Chronological,
+        }
+    }
+}
+
+/// A timer as it was just before a destructive action.
+struct Snapshot {
+    action: &'static str,
+    // Cloned with its `Instant`s, so restoring a running clock also brings back
+    // the time that passed since
+    stopwatch: Stopwatch,
+    started_at: DateTime<Local>,
+    alarm: Option<Duration>,
+    alarm_fired_at: Option<Instant>,
+    auto_stopped_at: Option<Instant>,
+    last_milestone: Option<u32>,
+    milestone_at: Option<Instant>,
+    frozen: Option<Duration>,
+    lap_sort: LapSort,
+    reference_lap: Option<usize>,
+    resume_lap_count: usize,
+    dirty: bool,
+}
+
+/// The TUI's state: every timer tab plus everything shared between them.
 struct App {
-    start_time: Instant,
-    is_running: bool,
-    laps: Vec<(Duration, Duration)>, // (lap_time, total_time)
-    last_lap: Instant,
-    laps_list_state: ListState,
+    tabs: Vec<TimerState>, // Never empty
+    active: usize,
+    new_timer_mode: Mode, // Mode for timers added with `+`
+    precision: Precision,
+    lap_precision: Precision,
+    display_format: DisplayFormat,
+    tick_rate: Duration,
+    theme: Theme,
+    keys: KeyBindings,
+    big_digits: bool,
+    second_bar: bool, // Show the bar that fills up over each second while running
+    window: usize,    // Laps in the rolling average, the latest ones
+    bell_on_lap: bool,
+    auto_follow: bool,                    // Select each new lap as it's recorded
+    lap_on_resume: bool,                  // Record a lap whenever a paused timer is resumed
+    quit_after: Option<Duration>,         // Quit once the active timer's elapsed reaches this
+    auto_stop: Option<Duration>,          // Pause each timer once its elapsed reaches this
+    milestone_interval: Option<Duration>, // Briefly highlight the timer at each multiple
+    ignore_suspend: bool,
+    mouse: bool, // Off with --no-mouse, leaving the terminal's own text selection working
+    minimal: bool, // Only the time is drawn
+    headless: bool, // Driven by `--commands` with no terminal, so no bell either
+    #[cfg(unix)]
+    suspend: Option<Suspend>, // None when headless, with no terminal to hand back
+    personal_best: PersonalBest, // Which record the statistics panel shows
+    records: Records, // Personal bests from earlier sessions
+    input_mode: InputMode,
+    input_buffer: String,
+    input_cursor: usize,            // Cursor position in `input_buffer`, in chars
+    command_action: Option<Action>, // Queued by the `:` prompt, run like a key press
+    status: Option<(String, Instant)>,
+    show_stats: bool,
+    show_graph: bool,
+    show_help: bool,
+    confirm_reset: bool,
+    confirm_quit: bool,
+    page_size: usize,
+    // Kept open for the whole run: on X11 the copied text is only available while
+    // its owner is alive
+    clipboard: Option<Clipboard>,
+    log: Option<BufWriter<File>>, // The `--log` file, if logging
+    laps_area: Rect,              // Where the lap rows were last drawn, for mouse hit-testing
+    second_bar_cells: u16,        // Length of the second bar as last drawn, 0 if it wasn't
 }
 
-impl App {
-    fn new() -> App {
-        let now = Instant::now();
-        App {
-            start_time: now,
-            is_running: true,
-            laps: Vec::new(),
-            last_lap: now,
-            laps_list_state: ListState::default(),
+impl TimerState {
+    fn new(stopwatch: Stopwatch) -> TimerState {
+        let mut laps_list_state = ListState::default();
+        if !stopwatch.laps().is_empty() {
+            laps_list_state.select(Some(0));
+        }
+        let started_at =
+            Local::now() - TimeDelta::from_std(stopwatch.elapsed()).unwrap_or_default();
+        TimerState {
+            started_at,
+            countdown_alerted: stopwatch.countdown_finished(),
+            stopwatch,
+            alarm: None,
+            alarm_fired_at: None,
+            auto_stopped_at: None,
+            last_milestone: None,
+            milestone_at: None,
+            pomodoro: None,
+            interval: None,
+            frozen: None,
+            laps_list_state,
+            lap_sort: LapSort::Chronological,
+            reference_lap: None,
+            resume_lap_count: 0,
+            history: Vec::new(),
+            dirty: false,
+        }
+    }
+
+    /// Remembers the timer's current state so `undo` can bring it back after `action`.
+    fn save_undo(&mut self, action: &'static str) {
+        if self.history.len() == UNDO_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(Snapshot {
+            action,
+            stopwatch: self.stopwatch.clone(),
+            started_at: self.started_at,
+            alarm: self.alarm,
+            alarm_fired_at: self.alarm_fired_at,
+            auto_stopped_at: self.auto_stopped_at,
+            last_milestone: self.last_milestone,
+            milestone_at: self.milestone_at,
+            frozen: self.frozen,
+            lap_sort: self.lap_sort,
+            reference_lap: self.reference_lap,
+            resume_lap_count: self.resume_lap_count,
+            dirty: self.dirty,
+        });
+    }
+
+    /// Restores the most recent snapshot, returning the action it undid.
+    fn undo(&mut self) -> Option<&'static str> {
+        let snapshot = self.history.pop()?;
+        self.stopwatch = snapshot.stopwatch;
+        self.started_at = snapshot.started_at;
+        self.alarm = snapshot.alarm;
+        self.alarm_fired_at = snapshot.alarm_fired_at;
+        self.auto_stopped_at = snapshot.auto_stopped_at;
+        self.last_milestone = snapshot.last_milestone;
+        self.milestone_at = snapshot.milestone_at;
+        self.frozen = snapshot.frozen;
+        self.lap_sort = snapshot.lap_sort;
+        self.reference_lap = snapshot.reference_lap;
+        self.resume_lap_count = snapshot.resume_lap_count;
+        self.dirty = snapshot.dirty;
+        self.countdown_alerted = self.stopwatch.countdown_finished();
+        let selected = (!self.stopwatch.laps().is_empty()).then_some(0);
+        self.laps_list_state.select(selected);
+        Some(snapshot.action)
+    }
+
+    /// Starts the Pomodoro's next phase as a fresh countdown. Does nothing without one.
+    fn advance_pomodoro(&mut self) {
+        let Some(pomodoro) = &mut self.pomodoro else {
+            return;
+        };
+        pomodoro.advance();
+        let target = pomodoro.duration();
+        self.stopwatch.set_mode(Mode::Countdown { target });
+        self.reset_clock();
+    }
+
+    /// Starts the interval's next round, unless that was the last one. Does nothing
+    /// without an interval.
+    fn advance_interval(&mut self) {
+        if self.interval.as_mut().is_some_and(Interval::advance) {
+            self.reset_clock();
+        }
+    }
+
+    /// Indices into `Stopwatch::laps` (oldest first) in the order the list shows
+    /// them: newest first, or by lap time with ties newest first.
+    fn display_order(&self) -> Vec<usize> {
+        let laps = self.stopwatch.laps();
+        let mut order: Vec<usize> = (0..laps.len()).rev().collect();
+        match self.lap_sort {
+            LapSort::Chronological => {}
+            LapSort::Fastest => order.sort_by_key(|&i| laps[i].0),
+            LapSort::Slowest => order.sort_by_key(|&i| Reverse(laps[i].0)),
+        }
+        order
+    }
+
+    /// What each row of the laps list shows: a lap from `display_order`, or `None`
+    /// for a phase heading. Headings only go between laps in the order they were
+    /// recorded, once the laps span more than one phase.
+    fn list_rows(&self) -> Vec<Option<usize>> {
+        let stopwatch = &self.stopwatch;
+        let order = self.display_order();
+        let phased = self.lap_sort == LapSort::Chronological
+            && order
+                .iter()
+                .any(|&i| stopwatch.lap_phase(i) != stopwatch.lap_phase(order[0]));
+        if !phased {
+            return order.into_iter().map(Some).collect();
+        }
+
+        let mut rows = Vec::new();
+        let mut phase = None;
+        for i in order {
+            if phase != Some(stopwatch.lap_phase(i)) {
+                phase = Some(stopwatch.lap_phase(i));
+                rows.push(None);
+            }
+            rows.push(Some(i));
         }
+        rows
     }
 
-    fn add_lap(&mut self) {
-        let now = Instant::now();
-        let lap_time = now.duration_since(self.last_lap);
-        let total_time = now.duration_since(self.start_time);
-        self.laps.push((lap_time, total_time));
-        self.last_lap = now;
+    /// Index of the selected lap in `Stopwatch::laps` order (oldest first).
+    fn selected_lap_index(&self) -> Option<usize> {
+        let selected = self.laps_list_state.selected()?;
+        self.display_order().get(selected).copied()
+    }
+
+    /// Selects the lap at `index` in `Stopwatch::laps` order, wherever it's shown.
+    fn select_lap(&mut self, index: usize) {
+        let row = self.display_order().iter().position(|&i| i == index);
+        self.laps_list_state.select(row);
+    }
+
+    /// Switches to the next sort order, keeping the same lap selected.
+    fn cycle_lap_sort(&mut self) {
+        let selected = self.selected_lap_index();
+        self.lap_sort = self.lap_sort.next();
+        if let Some(index) = selected {
+            self.select_lap(index);
+        }
+    }
 
-        // Auto-select the newest lap (at index 0 after reversing)
-        if !self.laps.is_empty() {
-            self.laps_list_state.select(Some(0));
+    /// Records a lap and, with `follow`, selects it. Otherwise the selection stays
+    /// on the same lap, wherever the new one pushes it.
+    fn add_lap(&mut self, follow: bool) {
+        let selected = self.selected_lap_index();
+        self.stopwatch.add_lap();
+        self.dirty = true;
+
+        match selected {
+            Some(index) if !follow => self.select_lap(index),
+            _ => self.select_lap(self.stopwatch.laps().len() - 1),
         }
     }
 
-    fn toggle_pause(&mut self) {
-        self.is_running = !self.is_running;
-        if self.is_running {
-            let now = Instant::now();
-            let paused_duration = now.duration_since(self.last_lap);
-            self.start_time += paused_duration;
-            self.last_lap = now;
+    fn delete_selected_lap(&mut self) {
+        let (Some(selected), Some(index)) =
+            (self.laps_list_state.selected(), self.selected_lap_index())
+        else {
+            return;
+        };
+
+        self.save_undo("lap deletion");
+        self.stopwatch.delete_lap(index);
+        self.reference_lap = match self.reference_lap {
+            Some(reference) if reference == index => None,
+            Some(reference) if reference > index => Some(reference - 1),
+            reference => reference,
+        };
+
+        let len = self.stopwatch.laps().len();
+        if len == 0 {
+            self.laps_list_state.select(None);
+        } else {
+            self.laps_list_state.select(Some(selected.min(len - 1)));
         }
     }
 
     fn reset(&mut self) {
-        let now = Instant::now();
-        self.start_time = now;
-        self.last_lap = now;
-        self.is_running = true;
-        self.laps.clear();
+        self.stopwatch.reset();
+        self.started_at = Local::now();
+        self.countdown_alerted = false;
+        self.reference_lap = None;
+        self.resume_lap_count = 0;
+        self.frozen = None;
         self.laps_list_state.select(None);
     }
 
-    fn elapsed(&self) -> Duration {
-        if self.is_running {
-            Instant::now().duration_since(self.start_time)
-        } else {
-            self.last_lap.duration_since(self.start_time)
+    /// Zeroes the clock and starts it again like `reset`, keeping the laps.
+    fn reset_clock(&mut self) {
+        self.stopwatch.reset_clock();
+        self.started_at = Local::now();
+        self.countdown_alerted = false;
+    }
+
+    /// What the main timer shows: the frozen time if there is one, otherwise the
+    /// live `display_time`.
+    fn shown_time(&self) -> Duration {
+        self.frozen.unwrap_or_else(|| self.stopwatch.display_time())
+    }
+
+    /// How far the second bar has filled, or `None` while it's hidden: when paused,
+    /// and when frozen so nothing on screen moves.
+    fn second_bar_ratio(&self) -> Option<f64> {
+        (self.stopwatch.is_running() && self.frozen.is_none())
+            .then(|| self.stopwatch.elapsed().as_secs_f64().fract())
+    }
+
+    /// Laps recorded since the timer was last resumed, or since it started.
+    fn segment_laps(&self) -> usize {
+        self.stopwatch
+            .timed_laps()
+            .len()
+            .saturating_sub(self.resume_lap_count)
+    }
+
+    /// Laps recorded per minute of elapsed time, or `None` before there's anything to measure.
+    fn lap_rate(&self) -> Option<f64> {
+        let laps = self.stopwatch.timed_laps().len();
+        let minutes = self.stopwatch.elapsed().as_secs_f64() / 60.0;
+        (laps > 0 && minutes > 0.0).then(|| laps as f64 / minutes)
+    }
+
+    /// Time left until the alarm goes off, if one is set and it hasn't yet.
+    fn until_alarm(&self) -> Option<Duration> {
+        self.alarm?
+            .checked_sub(self.stopwatch.elapsed())
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Fires the alarm the first time elapsed crosses it. Returns true when it just went off.
+    /// Dropping back below the alarm (e.g. after a reset) re-arms it.
+    fn check_alarm(&mut self) -> bool {
+        let Some(alarm) = self.alarm else {
+            return false;
+        };
+        let past_alarm = self.stopwatch.elapsed() >= alarm;
+        match (past_alarm, self.alarm_fired_at) {
+            (true, None) => {
+                self.alarm_fired_at = Some(Instant::now());
+                true
+            }
+            (false, Some(_)) => {
+                self.alarm_fired_at = None;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Pauses the timer the first time elapsed reaches `limit`, leaving it free to be
+    /// resumed past it. Returns true when it just stopped. A reset re-arms it.
+    fn check_auto_stop(&mut self, limit: Duration) -> bool {
+        let past_limit = self.stopwatch.elapsed() >= limit;
+        match (past_limit, self.auto_stopped_at) {
+            (true, None) => {
+                self.stopwatch.pause_at(limit);
+                self.auto_stopped_at = Some(Instant::now());
+                true
+            }
+            (false, Some(_)) => {
+                self.auto_stopped_at = None;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Notes when elapsed passes another multiple of `interval`. Only one crossing is
+    /// seen per milestone however coarse the ticks, and a resumed session or a reset
+    /// just catches the count up without flashing.
+    fn check_milestone(&mut self, interval: Duration) {
+        let reached = (self.stopwatch.elapsed().as_nanos() / interval.as_nanos()) as u32;
+        if self.last_milestone.is_some_and(|last| reached > last) {
+            self.milestone_at = Some(Instant::now());
         }
+        self.last_milestone = Some(reached);
+    }
+
+    /// Whether the timer is still highlighted for a milestone it just passed.
+    fn milestone_flash_on(&self) -> bool {
+        self.milestone_at
+            .is_some_and(|at| at.elapsed() < MILESTONE_FLASH_DURATION)
+    }
+
+    /// Whether the timer border should currently be drawn in its flashing state.
+    fn border_flash_on(&self) -> bool {
+        [self.alarm_fired_at, self.auto_stopped_at]
+            .into_iter()
+            .any(|fired_at| flash_state(fired_at) == Some(true))
+    }
+
+    /// Whether the alarm or auto-stop flash is still animating.
+    fn is_flashing(&self) -> bool {
+        [self.alarm_fired_at, self.auto_stopped_at]
+            .into_iter()
+            .any(|fired_at| flash_state(fired_at).is_some())
     }
 
     fn scroll_up(&mut self) {
-        if self.laps.is_empty() {
+        let len = self.stopwatch.laps().len();
+        if len == 0 {
             return;
         }
 
         let selected = match self.laps_list_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.laps.len() - 1
+                    len - 1
                 } else {
                     i - 1
                 }
@@ -95,13 +664,14 @@ impl App {
     }
 
     fn scroll_down(&mut self) {
-        if self.laps.is_empty() {
+        let len = self.stopwatch.laps().len();
+        if len == 0 {
             return;
         }
 
         let selected = match self.laps_list_state.selected() {
             Some(i) => {
-                if i >= self.laps.len() - 1 {
+                if i >= len - 1 {
                     0
                 } else {
                     i + 1
@@ -111,190 +681,3401 @@ impl App {
         };
         self.laps_list_state.select(Some(selected));
     }
-}
 
-fn format_duration(duration: Duration) -> String {
-    let total_seconds = duration.as_secs_f64();
-    if total_seconds < 60.0 {
-        format!("{:.2}s", total_seconds)
-    } else if total_seconds < 3600.0 {
-        let minutes = (total_seconds / 60.0).floor();
-        let seconds = total_seconds % 60.0;
-        format!("{:.0}m {:.2}s", minutes, seconds)
-    } else {
-        let hours = (total_seconds / 3600.0).floor();
-        let remaining_seconds = total_seconds % 3600.0;
-        let minutes = (remaining_seconds / 60.0).floor();
-        let seconds = remaining_seconds % 60.0;
-        format!("{:.0}h {:.0}m {:.1}s", hours, minutes, seconds)
+    /// Moves the selection a page towards newer laps, stopping at the newest.
+    fn page_up(&mut self, page_size: usize) {
+        if self.stopwatch.laps().is_empty() {
+            return;
+        }
+
+        let selected = match self.laps_list_state.selected() {
+            Some(i) => i.saturating_sub(page_size),
+            None => 0,
+        };
+        self.laps_list_state.select(Some(selected));
     }
-}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    /// Moves the selection a page towards older laps, stopping at the oldest.
+    fn page_down(&mut self, page_size: usize) {
+        let len = self.stopwatch.laps().len();
+        if len == 0 {
+            return;
+        }
+
+        let selected = match self.laps_list_state.selected() {
+            Some(i) => (i + page_size).min(len - 1),
+            None => 0,
+        };
+        self.laps_list_state.select(Some(selected));
+    }
 
-    // Create app and run it
-    let app = App::new();
-    let res = run_app(&mut terminal, app);
+    /// Selects the newest lap, which is drawn at the top of the list unless it's sorted.
+    fn scroll_to_top(&mut self) {
+        if let Some(newest) = self.stopwatch.laps().len().checked_sub(1) {
+            self.select_lap(newest);
+        }
+    }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    /// Selects the oldest lap, which is drawn at the bottom of the list unless it's sorted.
+    fn scroll_to_bottom(&mut self) {
+        if !self.stopwatch.laps().is_empty() {
+            self.select_lap(0);
+        }
+    }
 
-    if let Err(err) = res {
-        println!("{:?}", err)
+    /// Selects the quickest lap, the earliest one if several tie. A start lap
+    /// doesn't count.
+    fn select_fastest_lap(&mut self) {
+        // `min_by_key` keeps the first of equal keys
+        if let Some((index, _)) = self.timed_laps().min_by_key(|(_, lap)| lap.0) {
+            self.select_lap(index);
+        }
     }
 
-    Ok(())
+    /// Selects the slowest lap, the earliest one if several tie. A start lap
+    /// doesn't count.
+    fn select_slowest_lap(&mut self) {
+        // `max_by_key` keeps the last of equal keys, so search newest first
+        if let Some((index, _)) = self.timed_laps().rev().max_by_key(|(_, lap)| lap.0) {
+            self.select_lap(index);
+        }
+    }
+
+    /// `Stopwatch::timed_laps` with their indices into `Stopwatch::laps`.
+    fn timed_laps(&self) -> impl DoubleEndedIterator<Item = (usize, (Duration, Duration))> + '_ {
+        let first = self.stopwatch.laps().len() - self.stopwatch.timed_laps().len();
+        self.stopwatch
+            .timed_laps()
+            .iter()
+            .copied()
+            .enumerate()
+            .map(move |(i, lap)| (first + i, lap))
+    }
 }
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, &mut app))?;
 
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                // Only handle key press events, not key release or repeat
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                        KeyCode::Char(' ') => app.add_lap(),
-                        KeyCode::Char('p') => app.toggle_pause(),
-                        KeyCode::Char('r') => app.reset(),
-                        KeyCode::Up => app.scroll_up(),
-                        KeyCode::Down => app.scroll_down(),
-                        _ => {}
-                    }
-                }
-            }
+impl App {
+    /// Creates the UI with one tab per stopwatch; `stopwatches` must not be empty.
+    fn new(stopwatches: Vec<Stopwatch>) -> App {
+        App {
+            new_timer_mode: Mode::Stopwatch,
+            tabs: stopwatches.into_iter().map(TimerState::new).collect(),
+            active: 0,
+            precision: Precision::Hundredths,
+            lap_precision: Precision::Hundredths,
+            display_format: DisplayFormat::Words,
+            tick_rate: TICK_RATE,
+            theme: Theme::default(),
+            keys: KeyBindings::default(),
+            big_digits: false,
+            second_bar: true,
+            window: 5,
+            bell_on_lap: false,
+            auto_follow: true,
+            lap_on_resume: false,
+            quit_after: None,
+            auto_stop: None,
+            milestone_interval: None,
+            ignore_suspend: false,
+            mouse: true,
+            minimal: false,
+            headless: false,
+            #[cfg(unix)]
+            suspend: None,
+            personal_best: PersonalBest::Average,
+            records: Records::default(),
+            input_mode: InputMode::Normal,
+            input_buffer: String::new(),
+            input_cursor: 0,
+            command_action: None,
+            status: None,
+            show_stats: false,
+            show_graph: false,
+            show_help: false,
+            confirm_reset: false,
+            confirm_quit: false,
+            page_size: DEFAULT_PAGE_SIZE,
+            clipboard: None,
+            log: None,
+            laps_area: Rect::default(),
+            second_bar_cells: 0,
         }
     }
-}
 
-fn ui(f: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(2)
-        .constraints([
-            Constraint::Length(3), // Title
-            Constraint::Length(5), // Main timer
-            Constraint::Length(3), // Status/controls
-            Constraint::Min(0),    // Laps list
-        ])
-        .split(f.area());
+    /// The chosen personal best for the statistics panel, e.g. `avg 1m 2.30s`.
+    fn personal_best_text(&self) -> Option<String> {
+        match self.personal_best {
+            PersonalBest::Average => self
+                .records
+                .fastest_average
+                .map(|avg| format!("avg {}", self.format_lap_time(avg))),
+            PersonalBest::Laps => self.records.most_laps.map(|laps| format!("{} laps", laps)),
+        }
+    }
 
-    // Title
-    let title = Paragraph::new("⏱️  STOPWATCH")
-        .style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(title, chunks[0]);
+    fn timer(&self) -> &TimerState {
+        &self.tabs[self.active]
+    }
 
-    // Main timer display
-    let elapsed = app.elapsed();
-    let elapsed_str = format_duration(elapsed);
-
-    let timer_color = if elapsed.as_secs() < 10 {
-        Color::Green
-    } else if elapsed.as_secs() < 60 {
-        Color::Yellow
-    } else if elapsed.as_secs() < 300 {
-        Color::Cyan
-    } else {
-        Color::Magenta
-    };
+    fn timer_mut(&mut self) -> &mut TimerState {
+        &mut self.tabs[self.active]
+    }
 
-    let status_indicator = if app.is_running { "⏸" } else { "▶" };
+    /// Adds a fresh, running timer after the others and switches to it.
+    fn add_timer(&mut self) {
+        self.tabs
+            .push(TimerState::new(Stopwatch::new(self.new_timer_mode)));
+        self.active = self.tabs.len() - 1;
+    }
 
-    let timer_text = vec![Line::from(vec![
-        Span::styled(status_indicator, Style::default().fg(Color::White)),
-        Span::raw("  "),
-        Span::styled(
-            elapsed_str,
-            Style::default()
-                .fg(timer_color)
-                .add_modifier(Modifier::BOLD),
-        ),
-    ])];
+    /// Removes the active timer, unless it's the only one left.
+    fn remove_timer(&mut self) {
+        if self.tabs.len() == 1 {
+            self.set_status("Can't remove the last timer");
+            return;
+        }
+        self.tabs.remove(self.active);
+        self.active = self.active.min(self.tabs.len() - 1);
+    }
 
-    let timer = Paragraph::new(timer_text)
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).title("Elapsed Time"));
-    f.render_widget(timer, chunks[1]);
+    fn next_timer(&mut self) {
+        self.active = (self.active + 1) % self.tabs.len();
+    }
 
-    // Controls
-    let controls = if app.is_running {
-        "SPACE: Lap  •  P: Pause  •  R: Reset  •  ↑↓: Scroll  •  Q: Quit"
-    } else {
-        "P: Resume  •  R: Reset  •  ↑↓: Scroll  •  Q: Quit"
-    };
+    fn previous_timer(&mut self) {
+        self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+    }
 
-    let controls_widget = Paragraph::new(controls)
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).title("Controls"));
-    f.render_widget(controls_widget, chunks[2]);
+    /// Holds the shown time still, or lets it run live again, without touching the clock.
+    fn toggle_freeze(&mut self) {
+        let timer = self.timer_mut();
+        timer.frozen = match timer.frozen {
+            Some(_) => None,
+            None => Some(timer.stopwatch.display_time()),
+        };
+        if timer.frozen.is_some() {
+            self.set_status("Display frozen, the clock is still running");
+        } else {
+            self.set_status("Display live again");
+        }
+    }
 
-    // Laps list
-    if !app.laps.is_empty() {
-        let laps: Vec<ListItem> = app
-            .laps
+    /// Zeroes the clock and starts it again, keeping the laps as they were.
+    fn reset_clock(&mut self) {
+        let timer = self.timer_mut();
+        timer.save_undo("clock reset");
+        timer.reset_clock();
+        self.log(self.active, "clock reset");
+        self.set_status("Clock reset, laps kept");
+    }
+
+    /// Empties the lap history without touching the clock.
+    fn clear_laps(&mut self) {
+        let timer = self.timer_mut();
+        let count = timer.stopwatch.laps().len();
+        timer.save_undo("clearing laps");
+        timer.stopwatch.clear_laps();
+        timer.reference_lap = None;
+        timer.resume_lap_count = 0;
+        timer.laps_list_state.select(None);
+        self.set_status(format!("Cleared {} laps", count));
+    }
+
+    /// Moves the countdown's target by `COUNTDOWN_STEP`, never below the time
+    /// already elapsed, so what's left bottoms out at zero.
+    fn adjust_countdown(&mut self, extend: bool) {
+        let timer = self.timer_mut();
+        let Mode::Countdown { target } = timer.stopwatch.mode() else {
+            self.set_status("Not a countdown");
+            return;
+        };
+        let target = if extend {
+            target + COUNTDOWN_STEP
+        } else {
+            target
+                .saturating_sub(COUNTDOWN_STEP)
+                .max(timer.stopwatch.elapsed())
+        };
+        timer.stopwatch.set_mode(Mode::Countdown { target });
+        // Extending a finished countdown re-arms its bell; cutting one down to zero
+        // finishes it as usual
+        if !timer.stopwatch.countdown_finished() {
+            timer.countdown_alerted = false;
+        }
+        let left = timer.stopwatch.display_time();
+        self.set_status(format!("{} left", self.format_time(left)));
+    }
+
+    /// Makes the selected lap the reference, or clears it if it already is.
+    fn toggle_reference_lap(&mut self) {
+        let timer = self.timer_mut();
+        let Some(index) = timer.selected_lap_index() else {
+            self.set_status("No lap selected");
+            return;
+        };
+        if timer.reference_lap == Some(index) {
+            timer.reference_lap = None;
+            self.set_status("Reference lap cleared");
+        } else {
+            timer.reference_lap = Some(index);
+            self.set_status(format!("Comparing laps against Lap {}", index + 1));
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.timer_mut().undo() {
+            Some(action) => self.set_status(format!("Undid {}", action)),
+            None => self.set_status("Nothing to undo"),
+        }
+    }
+
+    /// `duration` in the chosen display format and precision.
+    fn format_time(&self, duration: Duration) -> String {
+        self.display_format.format(duration, self.precision)
+    }
+
+    /// `duration` in the chosen display format and lap precision.
+    fn format_lap_time(&self, duration: Duration) -> String {
+        self.display_format.format(duration, self.lap_precision)
+    }
+
+    /// Average of the active timer's last `window` lap times, or of all of them
+    /// while there are fewer; `None` with no laps.
+    fn recent_avg(&self) -> Option<Duration> {
+        let laps = self.timer().stopwatch.timed_laps();
+        let recent = &laps[laps.len().saturating_sub(self.window)..];
+        let total: Duration = recent.iter().map(|(lap_time, _)| *lap_time).sum();
+        (!recent.is_empty()).then(|| total / recent.len() as u32)
+    }
+
+    /// Whether any timer has laps recorded since it was last exported.
+    fn has_unexported_laps(&self) -> bool {
+        self.tabs
             .iter()
-            .enumerate()
-            .rev()
-            .map(|(i, (lap_time, total_time))| {
-                let lap_num = i + 1; // Correct lap numbering: first lap = 1, second = 2, etc.
-                ListItem::new(Line::from(vec![
-                    Span::styled(
-                        format!("Lap {:2}: ", lap_num),
-                        Style::default().fg(Color::Yellow),
-                    ),
-                    Span::styled(
-                        format_duration(*lap_time),
-                        Style::default().fg(Color::White),
-                    ),
-                    Span::raw("  (Total: "),
-                    Span::styled(
-                        format_duration(*total_time),
-                        Style::default().fg(Color::Gray),
-                    ),
-                    Span::raw(")"),
-                ]))
-            })
-            .collect();
+            .any(|timer| timer.dirty && !timer.stopwatch.laps().is_empty())
+    }
 
-        let laps_widget = List::new(laps)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(format!("Laps ({}) - Use ↑↓ to scroll", app.laps.len())),
-            )
-            .highlight_style(
-                Style::default()
-                    .bg(Color::Blue)
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            );
+    /// Puts `text` on the system clipboard, connecting to it on first use.
+    fn set_clipboard(&mut self, text: String) -> Result<(), arboard::Error> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(Clipboard::new()?);
+        }
+        match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(text),
+            None => Ok(()),
+        }
+    }
 
-        f.render_stateful_widget(laps_widget, chunks[3], &mut app.laps_list_state);
-    } else {
-        let no_laps = Paragraph::new("Press SPACE to record your first lap!")
-            .style(Style::default().fg(Color::Gray))
-            .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("Laps"));
-        f.render_widget(no_laps, chunks[3]);
+    /// Opens a prompt with `initial` already typed and the cursor at its end.
+    fn start_input(&mut self, mode: InputMode, initial: &str) {
+        self.input_mode = mode;
+        self.input_buffer = initial.to_string();
+        self.input_cursor = initial.chars().count();
+    }
+
+    /// Byte offset of the cursor in `input_buffer`.
+    fn input_byte_index(&self) -> usize {
+        self.input_buffer
+            .char_indices()
+            .nth(self.input_cursor)
+            .map_or(self.input_buffer.len(), |(i, _)| i)
+    }
+
+    /// Edits the open prompt: typing inserts at the cursor, Enter commits, Esc cancels.
+    fn handle_input_key(&mut self, code: KeyCode) {
+        let len = self.input_buffer.chars().count();
+        match code {
+            KeyCode::Char(c) => {
+                let index = self.input_byte_index();
+                self.input_buffer.insert(index, c);
+                self.input_cursor += 1;
+            }
+            KeyCode::Backspace if self.input_cursor > 0 => {
+                self.input_cursor -= 1;
+                let index = self.input_byte_index();
+                self.input_buffer.remove(index);
+            }
+            KeyCode::Delete if self.input_cursor < len => {
+                let index = self.input_byte_index();
+                self.input_buffer.remove(index);
+            }
+            KeyCode::Left => self.input_cursor = self.input_cursor.saturating_sub(1),
+            KeyCode::Right => self.input_cursor = (self.input_cursor + 1).min(len),
+            KeyCode::Home => self.input_cursor = 0,
+            KeyCode::End => self.input_cursor = len,
+            KeyCode::Enter => {
+                let mode = std::mem::replace(&mut self.input_mode, InputMode::Normal);
+                let input = std::mem::take(&mut self.input_buffer);
+                self.commit_input(mode, &input);
+            }
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.input_buffer.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn commit_input(&mut self, mode: InputMode, input: &str) {
+        match mode {
+            InputMode::Normal => {}
+            InputMode::Alarm => self.commit_alarm_input(input),
+            InputMode::SessionName => {
+                self.timer_mut().stopwatch.set_name(input);
+                match self.timer().stopwatch.name() {
+                    Some(name) => self.set_status(format!("Session named {}", name)),
+                    None => self.set_status("Session name cleared"),
+                }
+            }
+            InputMode::LapLabel => {
+                let Some(index) = self.timer().selected_lap_index() else {
+                    return;
+                };
+                self.timer_mut().stopwatch.set_lap_label(index, input);
+                match self.timer().stopwatch.lap_label(index) {
+                    Some(label) => self.set_status(format!("Lap {} labelled {}", index + 1, label)),
+                    None => self.set_status(format!("Lap {} label cleared", index + 1)),
+                }
+            }
+            InputMode::ImportPath => self.import_laps(input),
+            InputMode::Command => {
+                if let Err(err) = self.run_command(input) {
+                    self.set_status(err);
+                }
+            }
+        }
+    }
+
+    /// Runs a line typed at the `:` prompt: an action's config name, such as `lap`
+    /// or `toggle_stats`, which is queued in `command_action` as though its key was
+    /// pressed, or one of the commands that take an argument.
+    fn run_command(&mut self, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        let (command, arg) = input.split_once(' ').unwrap_or((input, ""));
+        let arg = arg.trim();
+        match command {
+            "" => {}
+            "countdown" => {
+                let target = parse_duration(arg)
+                    .map_err(|err| format!("Invalid countdown duration: {}", err))?;
+                let timer = self.timer_mut();
+                timer.save_undo("countdown");
+                timer.stopwatch.set_mode(Mode::Countdown { target });
+                timer.stopwatch.reset_clock();
+                timer.started_at = Local::now();
+                timer.countdown_alerted = false;
+                self.set_status(format!("Counting down from {}", self.format_time(target)));
+            }
+            "stopwatch" => {
+                self.timer_mut().stopwatch.set_mode(Mode::Stopwatch);
+                self.set_status("Counting up");
+            }
+            "export" => {
+                self.command_action = Some(match arg {
+                    "" | "csv" => Action::Export,
+                    "markdown" | "md" => Action::ExportMarkdown,
+                    _ => return Err(format!("Can't export to {}, only csv or markdown", arg)),
+                });
+            }
+            "import" => self.import_laps(arg),
+            "theme" => {
+                self.theme.set_palette(arg)?;
+                self.set_status(format!("Theme: {}", self.theme.palette.name));
+            }
+            "alarm" => self.commit_alarm_input(arg),
+            "name" => {
+                self.timer_mut().stopwatch.set_name(arg);
+                match self.timer().stopwatch.name() {
+                    Some(name) => self.set_status(format!("Session named {}", name)),
+                    None => self.set_status("Session name cleared"),
+                }
+            }
+            "label" => {
+                let (lap, label) = arg.split_once(' ').unwrap_or((arg, ""));
+                let index = lap
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|lap| lap.checked_sub(1))
+                    .filter(|&index| index < self.timer().stopwatch.laps().len())
+                    .ok_or_else(|| format!("No lap {:?}", lap))?;
+                self.timer_mut().stopwatch.set_lap_label(index, label);
+                match self.timer().stopwatch.lap_label(index) {
+                    Some(label) => self.set_status(format!("Lap {} labelled {}", index + 1, label)),
+                    None => self.set_status(format!("Lap {} label cleared", index + 1)),
+                }
+            }
+            _ => {
+                let action = Action::deserialize(command.into_deserializer()).map_err(
+                    |_: serde::de::value::Error| {
+                        format!(
+                            "Unknown command: {} (try lap, countdown 5m, export csv, theme mono)",
+                            command
+                        )
+                    },
+                )?;
+                if !arg.is_empty() {
+                    return Err(format!("{} takes no argument", command));
+                }
+                self.command_action = Some(action);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the active timer's laps with those in the CSV file at `path`.
+    fn import_laps(&mut self, path: &str) {
+        let path = path.trim();
+        if path.is_empty() {
+            return;
+        }
+        let laps = match File::open(path).and_then(|mut file| import_csv(&mut file)) {
+            Ok(laps) => laps,
+            Err(err) => {
+                self.set_status(format!("Import failed: {}: {}", path, err));
+                return;
+            }
+        };
+
+        let count = laps.len();
+        let timer = self.timer_mut();
+        timer.save_undo("import");
+        if let Err(err) = timer.stopwatch.replace_laps(laps) {
+            timer.history.pop();
+            self.set_status(format!("Import failed: {}: {}", path, err));
+            return;
+        }
+        timer.started_at =
+            Local::now() - TimeDelta::from_std(timer.stopwatch.elapsed()).unwrap_or_default();
+        timer.countdown_alerted = timer.stopwatch.countdown_finished();
+        timer.dirty = false;
+        timer.reference_lap = None;
+        let selected = (count > 0).then_some(0);
+        timer.laps_list_state.select(selected);
+        self.set_status(format!("Imported {} laps from {}", count, path));
+    }
+
+    /// Applies the alarm prompt's text: a duration sets the alarm, empty clears it.
+    fn commit_alarm_input(&mut self, input: &str) {
+        if input.trim().is_empty() {
+            let timer = self.timer_mut();
+            timer.alarm = None;
+            timer.alarm_fired_at = None;
+            self.set_status("Alarm cleared");
+            return;
+        }
+        match parse_duration(input) {
+            Ok(alarm) => {
+                let timer = self.timer_mut();
+                timer.alarm = Some(alarm);
+                timer.alarm_fired_at = None;
+                let alarm = self.format_time(alarm);
+                self.set_status(format!("Alarm set for {}", alarm));
+            }
+            Err(err) => self.set_status(format!("Invalid alarm time: {}", err)),
+        }
+    }
+
+    /// Appends `event` for the timer at `tab` to the `--log` file, if there is one.
+    /// A failed write is reported once and turns logging off.
+    fn log(&mut self, tab: usize, event: &str) {
+        let Some(log) = &mut self.log else {
+            return;
+        };
+        let timestamp = Local::now().format(LOG_TIME_FORMAT);
+        let result = if self.tabs.len() > 1 {
+            writeln!(log, "{} timer {}: {}", timestamp, tab + 1, event)
+        } else {
+            writeln!(log, "{} {}", timestamp, event)
+        };
+        if let Err(err) = result.and_then(|()| log.flush()) {
+            self.log = None;
+            self.set_status(format!("Stopped logging: {}", err));
+        }
+    }
+
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now()));
+    }
+
+    fn status_message(&self) -> Option<&str> {
+        match &self.status {
+            Some((message, shown_at)) if shown_at.elapsed() < STATUS_TIMEOUT => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Drops the status message once it has timed out. Returns true if one was removed.
+    fn expire_status(&mut self) -> bool {
+        if self.status.is_some() && self.status_message().is_none() {
+            self.status = None;
+            return true;
+        }
+        false
+    }
+
+    /// Selects the lap drawn at the given screen cell, if any.
+    fn select_lap_at(&mut self, column: u16, row: u16) {
+        if !self.laps_area.contains(Position::new(column, row)) {
+            return;
+        }
+
+        // Phase headings can't be selected
+        let row = usize::from(row - self.laps_area.y);
+        let timer = self.timer_mut();
+        let clicked = timer.laps_list_state.offset() + row;
+        if let Some(&Some(index)) = timer.list_rows().get(clicked) {
+            timer.select_lap(index);
+        }
+    }
+}
+
+/// For a flash started at `fired_at`: `None` once it's over, otherwise whether it's
+/// in the lit half of its blink.
+fn flash_state(fired_at: Option<Instant>) -> Option<bool> {
+    let since = fired_at?.elapsed();
+    (since < ALARM_FLASH_DURATION).then(|| (since.as_millis() / 250).is_multiple_of(2))
+}
+
+/// File formats the laps can be exported to.
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Writes the active timer's laps in `format`, the same for a file or the clipboard.
+fn write_laps(app: &App, format: ExportFormat, writer: &mut impl Write) -> io::Result<()> {
+    let stopwatch = &app.timer().stopwatch;
+    match format {
+        ExportFormat::Csv => export_csv(stopwatch, writer),
+        ExportFormat::Markdown => export_markdown(stopwatch, app.lap_precision, writer),
+    }
+}
+
+/// Exports the laps to `laps-<unix timestamp>.<extension>` in the current
+/// directory and reports the outcome in the status line.
+fn export_laps_file(app: &mut App, format: ExportFormat) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let extension = format.extension();
+    let path = match app.timer().stopwatch.name() {
+        Some(name) => PathBuf::from(format!(
+            "laps-{}-{}.{}",
+            file_slug(name),
+            timestamp,
+            extension
+        )),
+        None => PathBuf::from(format!("laps-{}.{}", timestamp, extension)),
+    };
+
+    let result = fs::File::create(&path).and_then(|file| {
+        let mut writer = io::BufWriter::new(file);
+        write_laps(app, format, &mut writer)?;
+        writer.flush()
+    });
+    match result {
+        Ok(()) => {
+            app.timer_mut().dirty = false;
+            app.set_status(format!(
+                "Exported {} laps to {}",
+                app.timer().stopwatch.laps().len(),
+                path.display()
+            ))
+        }
+        Err(err) => app.set_status(format!("Export failed: {}", err)),
+    }
+}
+
+/// Copies the selected lap as a single line, e.g. `Lap 3: 11.23s (-4.44s), split 39.24s`.
+fn copy_selected_lap(app: &mut App) {
+    let Some(index) = app.timer().selected_lap_index() else {
+        app.set_status("No lap selected");
+        return;
+    };
+
+    let row = lap_rows(
+        &app.timer().stopwatch,
+        app.lap_precision,
+        app.display_format,
+        &app.theme.palette,
+    )
+    .swap_remove(index);
+    let text = match &row.delta {
+        Some((delta, _)) => format!(
+            "{}: {} ({}), split {}",
+            row.lap, row.lap_time, delta, row.split
+        ),
+        None => format!("{}: {}, split {}", row.lap, row.lap_time, row.split),
+    };
+    match app.set_clipboard(text) {
+        Ok(()) => app.set_status(format!("Copied {}!", row.lap)),
+        Err(err) => app.set_status(format!("Clipboard unavailable: {}", err)),
+    }
+}
+
+/// Copies every lap, oldest first, as the same aligned table the laps list shows.
+fn copy_all_laps(app: &mut App) {
+    let laps = app.timer().stopwatch.laps();
+    if laps.is_empty() {
+        app.set_status("No laps to copy");
+        return;
+    }
+
+    let rows = lap_rows(
+        &app.timer().stopwatch,
+        app.lap_precision,
+        app.display_format,
+        &app.theme.palette,
+    );
+    let widths = column_widths(&rows);
+    let lines = std::iter::once(LAP_HEADERS)
+        .chain(rows.iter().map(LapRow::cells))
+        .map(|cells| {
+            pad_cells(cells, widths)
+                .join(COLUMN_GAP)
+                .trim_end()
+                .to_string()
+        });
+    let text = lines.collect::<Vec<_>>().join("\n");
+    let count = rows.len();
+    match app.set_clipboard(text) {
+        Ok(()) => app.set_status(format!("Copied {} laps!", count)),
+        Err(err) => app.set_status(format!("Clipboard unavailable: {}", err)),
+    }
+}
+
+/// Copies every lap as the CSV `export_csv` writes, ready to paste into a spreadsheet.
+fn copy_laps_csv(app: &mut App) {
+    let count = app.timer().stopwatch.laps().len();
+    if count == 0 {
+        app.set_status("No laps to copy");
+        return;
+    }
+
+    let mut csv = Vec::new();
+    if let Err(err) = write_laps(app, ExportFormat::Csv, &mut csv) {
+        app.set_status(format!("Export failed: {}", err));
+        return;
+    }
+    let bytes = csv.len();
+    // `export_csv` only writes valid UTF-8
+    let text = String::from_utf8_lossy(&csv).into_owned();
+    match app.set_clipboard(text) {
+        Ok(()) => app.set_status(format!("Copied {} laps as CSV ({} bytes)", count, bytes)),
+        Err(err) => app.set_status(format!("Clipboard unavailable: {}", err)),
+    }
+}
+
+/// Copies the active timer's elapsed time, e.g. `5m 3.20s`.
+fn copy_elapsed(app: &mut App) {
+    let text = app.format_time(app.timer().stopwatch.elapsed());
+    match app.set_clipboard(text.clone()) {
+        Ok(()) => app.set_status(format!("Copied {}", text)),
+        Err(err) => app.set_status(format!("Clipboard unavailable: {}", err)),
+    }
+}
+
+/// Lowercase `name` with anything but letters and digits collapsed into single dashes,
+/// so it's safe to use in a file name.
+fn file_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Printed by `--help`. Keep in step with `parse_args` and the README's options table.
+const USAGE: &str = "\
+Usage: stopwatch [OPTIONS] [DURATION]
+
+A terminal stopwatch with laps. A DURATION such as 90s or 1h30m counts down
+from it, like --countdown.
+
+Options:
+  --countdown <DURATION>   Count down from DURATION instead of counting up
+  --big                    Start with the timer in big block digits
+  --bell-on-lap            Ring the terminal bell on every lap
+  --lap-on-resume          Record a lap whenever a paused timer is resumed
+  --lap-at-start           Start with a zero-length Lap 1 marking the start
+  --offset <DURATION>      Start with DURATION already on the clock
+  --interval <DURATION>    Count down DURATION over and over, in rounds
+  --rounds <N>             With --interval, stop after N rounds
+  --pomodoro               Alternate work and break countdowns
+  --auto-stop <DURATION>   Pause once the elapsed time reaches DURATION
+  --duration <DURATION>    Quit once the elapsed time reaches DURATION
+  --config <PATH>          Read settings from PATH
+  --data-dir <DIR>         Keep the saved session and personal bests in DIR
+  --log <PATH>             Append timer events to PATH
+  --commands <PATH>        Run without the interface, reading commands from
+                           PATH (- for stdin), then print the laps as JSON
+  --json                   Print the elapsed time and laps as JSON on quit
+  --ignore-suspend         Don't count time the machine spends suspended
+  --minimal                Start in minimal mode, showing only the time
+  --inline                 Draw below the prompt instead of the whole screen
+  --summary                Show the totals and lap statistics before exiting
+  --quiet                  Print nothing on exit except what was asked for
+  --no-color               Draw without colors
+  --no-mouse               Leave the mouse to the terminal
+  --start-paused           Start with the clock paused
+  --precision <LEVEL>      Fractional seconds: tenths, hundredths or milliseconds
+  --lap-precision <LEVEL>  Fractional seconds on laps, if different
+  --tick-rate <DURATION>   Redraw interval while running, 10ms-1s
+  -h, --help               Print this help and exit
+  -V, --version            Print the version and exit
+";
+
+struct Args {
+    mode: Mode,
+    precision: Option<Precision>,
+    lap_precision: Option<Precision>,
+    tick_rate: Option<Duration>,
+    big_digits: bool,
+    bell_on_lap: bool,
+    lap_on_resume: bool,
+    lap_at_start: bool,
+    offset: Option<Duration>,
+    summary: bool,
+    start_paused: bool,
+    ignore_suspend: bool,
+    no_mouse: bool,
+    no_color: bool,
+    quiet: bool,
+    minimal: bool,
+    inline: bool,
+    commands: Option<PathBuf>,
+    pomodoro: bool,
+    interval: Option<Duration>,
+    rounds: Option<u32>,
+    json: bool,
+    duration: Option<Duration>,
+    auto_stop: Option<Duration>,
+    log: Option<PathBuf>,
+    config: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        mode: Mode::Stopwatch,
+        precision: None,
+        lap_precision: None,
+        tick_rate: None,
+        big_digits: false,
+        bell_on_lap: false,
+        lap_on_resume: false,
+        lap_at_start: false,
+        offset: None,
+        summary: false,
+        start_paused: false,
+        ignore_suspend: false,
+        no_mouse: false,
+        no_color: false,
+        quiet: false,
+        minimal: false,
+        inline: false,
+        commands: None,
+        pomodoro: false,
+        interval: None,
+        rounds: None,
+        json: false,
+        duration: None,
+        auto_stop: None,
+        log: None,
+        config: None,
+        data_dir: None,
+    };
+
+    // A bare duration, as in `stopwatch 300`, is a countdown unless --countdown gives one
+    let mut positional_countdown = None;
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--countdown" => {
+                let value = iter
+                    .next()
+                    .ok_or("--countdown requires a duration, e.g. --countdown 5m")?;
+                let target = parse_duration(&value).map_err(|err| {
+                    format!("invalid duration for --countdown: {}: {}", value, err)
+                })?;
+                args.mode = Mode::Countdown { target };
+            }
+            "--precision" => {
+                let value = iter
+                    .next()
+                    .ok_or("--precision requires tenths, hundredths or milliseconds")?;
+                args.precision = Some(
+                    Precision::parse(&value)
+                        .ok_or_else(|| format!("invalid value for --precision: {}", value))?,
+                );
+            }
+            "--lap-precision" => {
+                let value = iter
+                    .next()
+                    .ok_or("--lap-precision requires tenths, hundredths or milliseconds")?;
+                args.lap_precision = Some(
+                    Precision::parse(&value)
+                        .ok_or_else(|| format!("invalid value for --lap-precision: {}", value))?,
+                );
+            }
+            "--tick-rate" => {
+                let value = iter
+                    .next()
+                    .ok_or("--tick-rate requires a duration, e.g. --tick-rate 100ms")?;
+                let tick_rate = parse_duration(&value).map_err(|err| {
+                    format!("invalid duration for --tick-rate: {}: {}", value, err)
+                })?;
+                args.tick_rate = Some(tick_rate);
+            }
+            "--big" => args.big_digits = true,
+            "--bell-on-lap" => args.bell_on_lap = true,
+            "--lap-on-resume" => args.lap_on_resume = true,
+            "--lap-at-start" => args.lap_at_start = true,
+            "--offset" => {
+                let value = iter
+                    .next()
+                    .ok_or("--offset requires a duration, e.g. --offset 10m")?;
+                let offset = parse_duration(&value)
+                    .map_err(|err| format!("invalid duration for --offset: {}: {}", value, err))?;
+                args.offset = Some(offset);
+            }
+            "--summary" => args.summary = true,
+            "--start-paused" => args.start_paused = true,
+            "--ignore-suspend" => args.ignore_suspend = true,
+            "--no-mouse" => args.no_mouse = true,
+            "--no-color" => args.no_color = true,
+            "--quiet" => args.quiet = true,
+            "--minimal" => args.minimal = true,
+            "--inline" => args.inline = true,
+            "--pomodoro" => args.pomodoro = true,
+            "--interval" => {
+                let value = iter
+                    .next()
+                    .ok_or("--interval requires a duration, e.g. --interval 30s")?;
+                let interval = parse_duration(&value).map_err(|err| {
+                    format!("invalid duration for --interval: {}: {}", value, err)
+                })?;
+                if interval.is_zero() {
+                    return Err("--interval must be longer than zero".to_string());
+                }
+                args.interval = Some(interval);
+            }
+            "--rounds" => {
+                let value = iter
+                    .next()
+                    .ok_or("--rounds requires a number, e.g. --rounds 8")?;
+                let rounds = value
+                    .parse()
+                    .ok()
+                    .filter(|&rounds: &u32| rounds > 0)
+                    .ok_or_else(|| format!("invalid value for --rounds: {}", value))?;
+                args.rounds = Some(rounds);
+            }
+            "--json" => args.json = true,
+            "--duration" => {
+                let value = iter
+                    .next()
+                    .ok_or("--duration requires a duration, e.g. --duration 30s")?;
+                let duration = parse_duration(&value).map_err(|err| {
+                    format!("invalid duration for --duration: {}: {}", value, err)
+                })?;
+                args.duration = Some(duration);
+            }
+            "--auto-stop" => {
+                let value = iter
+                    .next()
+                    .ok_or("--auto-stop requires a duration, e.g. --auto-stop 30s")?;
+                let limit = parse_duration(&value).map_err(|err| {
+                    format!("invalid duration for --auto-stop: {}: {}", value, err)
+                })?;
+                args.auto_stop = Some(limit);
+            }
+            "--commands" => {
+                let path = iter
+                    .next()
+                    .ok_or("--commands requires a file path, or - for stdin")?;
+                args.commands = Some(PathBuf::from(path));
+            }
+            "--log" => {
+                let path = iter.next().ok_or("--log requires a file path")?;
+                args.log = Some(PathBuf::from(path));
+            }
+            "--config" => {
+                let path = iter.next().ok_or("--config requires a file path")?;
+                args.config = Some(PathBuf::from(path));
+            }
+            "--data-dir" => {
+                let path = iter.next().ok_or("--data-dir requires a directory")?;
+                args.data_dir = Some(PathBuf::from(path));
+            }
+            // Handled as soon as they're seen, before the terminal is touched
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                process::exit(0);
+            }
+            "-V" | "--version" => {
+                println!("stopwatch {}", env!("CARGO_PKG_VERSION"));
+                process::exit(0);
+            }
+            other if !other.starts_with('-') => {
+                if positional_countdown.is_some() {
+                    return Err(format!("unexpected argument: {}", other));
+                }
+                let target = parse_duration(other)
+                    .map_err(|err| format!("invalid countdown duration: {}: {}", other, err))?;
+                positional_countdown = Some(target);
+            }
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    if let (Some(target), Mode::Stopwatch) = (positional_countdown, args.mode) {
+        args.mode = Mode::Countdown { target };
+    }
+    if args.pomodoro && args.mode != Mode::Stopwatch {
+        return Err("--pomodoro can't be combined with a countdown".to_string());
+    }
+    if args.interval.is_some() && (args.pomodoro || args.mode != Mode::Stopwatch) {
+        return Err("--interval can't be combined with --pomodoro or a countdown".to_string());
+    }
+    if args.rounds.is_some() && args.interval.is_none() {
+        return Err("--rounds needs --interval".to_string());
+    }
+
+    Ok(args)
+}
+
+/// Where the session and records are kept: `flag` (`--data-dir`), then
+/// `$STOPWATCH_DATA_DIR`, then the XDG base directory spec's data directory.
+fn data_dir(flag: Option<&Path>) -> Option<PathBuf> {
+    if let Some(dir) = flag {
+        return Some(dir.to_path_buf());
+    }
+    match env::var_os("STOPWATCH_DATA_DIR") {
+        Some(dir) if !dir.is_empty() => return Some(PathBuf::from(dir)),
+        _ => {}
+    }
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(data_dir.join("stopwatch"))
+}
+
+/// Asks on stdin whether to resume a saved session. Defaults to yes.
+fn prompt_resume(path: &Path) -> io::Result<bool> {
+    print!("Resume previous session from {}? [Y/n] ", path.display());
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(!answer.trim().eq_ignore_ascii_case("n"))
+}
+
+/// How the UI takes over the terminal.
+#[derive(Clone, Copy)]
+struct TerminalSetup {
+    mouse: bool,  // Capture the mouse; off with --no-mouse
+    inline: bool, // Draw in the normal buffer instead of the alternate screen (--inline)
+}
+
+/// Owns the terminal while the UI is running.
+///
+/// Creating it enables raw mode, the alternate screen and mouse capture;
+/// dropping it undoes all three. Because `Drop` also runs while unwinding,
+/// a panic in `run_app` or `ui` no longer leaves the user's shell unusable.
+/// A panic hook restores the terminal before the panic message is printed so
+/// the message isn't lost on the alternate screen.
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    setup: TerminalSetup,
+}
+
+impl TerminalGuard {
+    fn new(setup: TerminalSetup) -> io::Result<TerminalGuard> {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal(setup);
+            default_hook(info);
+        }));
+
+        enter_terminal(setup)?;
+        let backend = CrosstermBackend::new(io::stdout());
+        let terminal = if setup.inline {
+            Terminal::with_options(
+                backend,
+                TerminalOptions {
+                    viewport: Viewport::Inline(INLINE_HEIGHT),
+                },
+            )?
+        } else {
+            Terminal::new(backend)?
+        };
+        Ok(TerminalGuard { terminal, setup })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.setup.inline {
+            // Leave the last frame in the scrollback and start the shell prompt below it
+            let bottom = self.terminal.get_frame().area().bottom();
+            let _ = execute!(
+                io::stdout(),
+                MoveTo(0, bottom.saturating_sub(1)),
+                Print("\r\n")
+            );
+        }
+        restore_terminal(self.setup);
+    }
+}
+
+/// Turns on what `setup` asks for: raw mode, plus the alternate screen and mouse
+/// capture unless they're off.
+fn enter_terminal(setup: TerminalSetup) -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    if !setup.inline {
+        execute!(stdout, EnterAlternateScreen)?;
+    }
+    if setup.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+/// What it takes to hand the terminal back to the shell on Ctrl+Z and take it
+/// over again on `fg`.
+#[cfg(unix)]
+struct Suspend {
+    setup: TerminalSetup,
+    requested: Arc<AtomicBool>, // Set by a SIGTSTP from outside, e.g. `kill -TSTP`
+}
+
+/// Restores the terminal and stops the process the way SIGTSTP normally would,
+/// then, once the shell continues it, takes the terminal over and redraws it.
+#[cfg(unix)]
+fn suspend<B: Backend>(terminal: &mut Terminal<B>, setup: TerminalSetup) -> io::Result<()> {
+    restore_terminal(setup);
+    // Returns only once the process is continued
+    emulate_default_handler(SIGTSTP)?;
+    enter_terminal(setup)?;
+    terminal.clear()
+}
+
+/// Best-effort terminal restore. Safe to call more than once. Only undoes what
+/// `setup` turned on.
+fn restore_terminal(setup: TerminalSetup) {
+    let _ = disable_raw_mode();
+    if setup.mouse {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    if !setup.inline {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+    let _ = execute!(io::stdout(), Show);
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args()?;
+    let config = Config::load(args.config.as_deref())?;
+    let theme = Theme::from_config(&config, args.no_color)?;
+    let keys = KeyBindings::from_config(&config)?;
+    // Driven by commands there's no one to ask about resuming, and nothing worth saving
+    let data_dir = data_dir(args.data_dir.as_deref()).filter(|_| args.commands.is_none());
+    let session_path = data_dir.as_ref().map(|dir| dir.join("session.json"));
+    let records_path = data_dir.as_ref().map(|dir| dir.join("records.json"));
+
+    // Offer to pick up where the last session left off
+    let mut stopwatches = Vec::new();
+    if let Some(path) = session_path.as_deref().filter(|p| p.exists()) {
+        if prompt_resume(path)? {
+            match Stopwatch::load_all(path) {
+                Ok(loaded) => stopwatches = loaded,
+                Err(err) => eprintln!("Could not load session, starting fresh: {}", err),
+            }
+        }
+    }
+    let resumed = !stopwatches.is_empty();
+    let pomodoro = if args.pomodoro && !resumed {
+        Some(config.pomodoro.pomodoro()?)
+    } else {
+        None
+    };
+    let interval = args
+        .interval
+        .filter(|_| !resumed)
+        .map(|duration| Interval::new(duration, args.rounds));
+    if !resumed {
+        let mode = match (&pomodoro, &interval) {
+            (Some(pomodoro), _) => Mode::Countdown {
+                target: pomodoro.duration(),
+            },
+            (None, Some(interval)) => Mode::Countdown {
+                target: interval.duration,
+            },
+            (None, None) => args.mode,
+        };
+        let mut stopwatch = if args.start_paused {
+            Stopwatch::new_paused(mode)
+        } else {
+            Stopwatch::new(mode)
+        };
+        // A zero-length Lap 1 marks the start, so the first lap recorded is Lap 2
+        if args.lap_at_start {
+            stopwatch.add_start_lap();
+        }
+        // After the start lap, which stays at zero; the offset goes in the lap after it
+        if let Some(offset) = args.offset {
+            stopwatch.add_offset(offset)?;
+        }
+        stopwatches.push(stopwatch);
+    }
+    let mut app = App::new(stopwatches);
+    app.tabs[0].pomodoro = pomodoro;
+    app.tabs[0].interval = interval;
+    app.new_timer_mode = args.mode;
+    // Hundredths or milliseconds asked for explicitly also get the smoother tick rate
+    let precision = args.precision.or(config.precision()?);
+    let smooth = precision.is_some_and(|precision| precision != Precision::Tenths);
+    app.precision = precision.unwrap_or(Precision::Hundredths);
+    app.lap_precision = args
+        .lap_precision
+        .or(config.lap_precision()?)
+        .unwrap_or(app.precision);
+    app.display_format = config.display_format()?;
+    app.tick_rate = match args.tick_rate.or(config.tick_rate()?) {
+        Some(tick_rate) if !(MIN_TICK_RATE..=MAX_TICK_RATE).contains(&tick_rate) => {
+            return Err(format!(
+                "tick rate must be between {:?} and {:?}, got {:?}",
+                MIN_TICK_RATE, MAX_TICK_RATE, tick_rate
+            )
+            .into());
+        }
+        Some(tick_rate) => tick_rate,
+        None if smooth => SMOOTH_TICK_RATE,
+        None => TICK_RATE,
+    };
+    app.theme = theme;
+    app.keys = keys;
+    app.big_digits = args.big_digits;
+    app.second_bar = config.second_bar();
+    app.window = config.window()?;
+    app.bell_on_lap = args.bell_on_lap;
+    app.lap_on_resume = args.lap_on_resume;
+    app.quit_after = args.duration;
+    app.ignore_suspend = args.ignore_suspend;
+    app.mouse = !args.no_mouse;
+    app.minimal = args.minimal;
+    app.auto_stop = args.auto_stop;
+    app.milestone_interval = config.milestone_interval()?;
+    app.personal_best = config.personal_best()?;
+    if let Some(path) = &records_path {
+        // A missing or unreadable file just means no records yet
+        app.records = Records::load(path).unwrap_or_default();
+    }
+    if let Some(path) = &args.log {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => app.log = Some(BufWriter::new(file)),
+            Err(err) => app.set_status(format!("Not logging to {}: {}", path.display(), err)),
+        }
+    }
+    for tab in 0..app.tabs.len() {
+        if app.tabs[tab].stopwatch.is_running() {
+            app.log(tab, if resumed { "resumed" } else { "started" });
+        }
+    }
+
+    let res = if let Some(path) = &args.commands {
+        // Nothing is shown, so draw into memory and print the laps at the end
+        let reader: Box<dyn BufRead + Send> = if path.as_os_str() == "-" {
+            if io::stdin().is_terminal() {
+                eprintln!(
+                    "Reading commands from the terminal: lap, pause, reset or quit, one per line"
+                );
+            }
+            Box::new(BufReader::new(io::stdin()))
+        } else {
+            let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            Box::new(BufReader::new(file))
+        };
+        let mut events = CommandEvents::new(reader, &app.keys)?;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24))?;
+        app.headless = true;
+        run_app(&mut terminal, &mut events, &mut app)
+    } else {
+        // Setup terminal, run the app, then restore the terminal
+        let setup = TerminalSetup {
+            mouse: app.mouse,
+            inline: args.inline,
+        };
+        let mut guard = TerminalGuard::new(setup)?;
+        // A SIGTSTP from outside no longer stops the process straight away, leaving the
+        // terminal in raw mode; the main loop suspends properly on its next tick instead
+        #[cfg(unix)]
+        {
+            let requested = Arc::new(AtomicBool::new(false));
+            signal_hook::flag::register(SIGTSTP, Arc::clone(&requested))?;
+            app.suspend = Some(Suspend { setup, requested });
+        }
+        let res = run_app(&mut guard.terminal, &mut CrosstermEvents, &mut app).and_then(|()| {
+            if args.summary {
+                show_summary(&mut guard.terminal, &mut CrosstermEvents, &app)
+            } else {
+                Ok(())
+            }
+        });
+        drop(guard);
+        res
+    };
+
+    // The terminal is restored by now, so this can't be drawn over
+    if let Err(err) = &res {
+        if !args.quiet {
+            eprintln!("Error: {}", err);
+            let _ = io::stderr().flush();
+        }
+    }
+
+    // Printed only now the terminal is restored, so it isn't lost on the alternate screen
+    if args.json || args.commands.is_some() {
+        export_json(&app.timer().stopwatch, &mut io::stdout().lock())?;
+    }
+
+    if let Some(path) = &records_path {
+        let mut beaten = Vec::new();
+        for tab in &app.tabs {
+            beaten.extend(app.records.update(&tab.stopwatch));
+        }
+        if beaten.contains(&app.personal_best) && !args.quiet {
+            if let Some(text) = app.personal_best_text() {
+                println!("New personal best: {}", text);
+            }
+        }
+        if !beaten.is_empty() {
+            if let Err(err) = app.records.save(path) {
+                eprintln!("Could not save records to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    if let Some(path) = &session_path {
+        let stopwatches: Vec<Stopwatch> = app.tabs.into_iter().map(|tab| tab.stopwatch).collect();
+        if let Err(err) = Stopwatch::save_all(&stopwatches, path) {
+            eprintln!("Could not save session to {}: {}", path.display(), err);
+        }
+    }
+
+    // Everything is saved, so nothing is lost by exiting without unwinding
+    if res.is_err() {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Where `run_app` gets its input from, so the loop can be driven by
+/// something other than the real terminal.
+trait EventSource {
+    /// Waits up to `timeout` for an event, returning whether one is ready.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+
+    /// Returns the next event, blocking until one is available.
+    fn read(&mut self) -> io::Result<Event>;
+}
+
+/// Reads events from the terminal through crossterm.
+struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+}
+
+/// Reads line commands (`lap`, `pause`, `reset`, `quit`) from a file, pipe or
+/// stdin and turns each into the key presses it stands for, so scripts can drive
+/// the same loop as the keyboard. The end of the input quits.
+struct CommandEvents {
+    lines: Receiver<String>,
+    pending: VecDeque<Event>,
+    lap: KeyCode,
+    pause: KeyCode,
+    reset: KeyCode,
+}
+
+impl CommandEvents {
+    fn new(
+        reader: impl BufRead + Send + 'static,
+        keys: &KeyBindings,
+    ) -> Result<CommandEvents, String> {
+        let key = |action, command| {
+            keys.key(action)
+                .ok_or_else(|| format!("the {} command needs a key bound to {:?}", command, action))
+        };
+        let (lap, pause, reset) = (
+            key(Action::Lap, "lap")?,
+            key(Action::Pause, "pause")?,
+            key(Action::Reset, "reset")?,
+        );
+
+        // `poll` has to time out, which a blocking read can't, so read on a thread
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(CommandEvents {
+            lines,
+            pending: VecDeque::new(),
+            lap,
+            pause,
+            reset,
+        })
+    }
+
+    fn queue_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.pending
+            .push_back(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    fn queue_command(&mut self, line: &str) {
+        match line.trim() {
+            "" => {}
+            "lap" => self.queue_key(self.lap, KeyModifiers::NONE),
+            "pause" => self.queue_key(self.pause, KeyModifiers::NONE),
+            // Pressed twice, as a reset has to be confirmed
+            "reset" => {
+                self.queue_key(self.reset, KeyModifiers::NONE);
+                self.queue_key(self.reset, KeyModifiers::NONE);
+            }
+            // Ctrl-C, which quits straight away without asking about unexported laps
+            "quit" => self.queue_key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            other => eprintln!("Ignoring unknown command: {}", other),
+        }
+    }
+}
+
+impl EventSource for CommandEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.pending.is_empty() {
+            match self.lines.recv_timeout(timeout) {
+                Ok(line) => self.queue_command(&line),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => self.queue_command("quit"),
+            }
+        }
+        Ok(!self.pending.is_empty())
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        while self.pending.is_empty() {
+            match self.lines.recv() {
+                Ok(line) => self.queue_command(&line),
+                Err(_) => self.queue_command("quit"),
+            }
+        }
+        Ok(self.pending.pop_front().expect("just queued an event"))
+    }
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
+    app: &mut App,
+) -> io::Result<()> {
+    let mut needs_redraw = true;
+    let mut last_draw = Instant::now();
+    let mut last_frame_tick = Instant::now();
+    let mut last_frame = None;
+    let mut clock_second = Local::now().timestamp();
+    loop {
+        #[cfg(unix)]
+        if let Some(Suspend { setup, requested }) = &app.suspend {
+            if requested.swap(false, Ordering::Relaxed) {
+                suspend(terminal, *setup)?;
+                needs_redraw = true;
+            }
+        }
+
+        if app.expire_status() {
+            needs_redraw = true;
+        }
+        // Keep the time of day in the title bar ticking even while paused
+        let now_second = Local::now().timestamp();
+        if now_second != clock_second {
+            clock_second = now_second;
+            needs_redraw = true;
+        }
+
+        // A paused stopwatch shows nothing new, so only redraw it after input
+        // or while its border is flashing. A running one is redrawn once per
+        // tick, however many events arrive in between.
+        let animating = app.timer().stopwatch.is_running() || app.timer().is_flashing();
+        if needs_redraw || (animating && last_frame_tick.elapsed() >= app.tick_rate) {
+            last_frame_tick = Instant::now();
+            // Ticks faster than the shown precision would redraw the same frame, which
+            // slow links (e.g. SSH) pay for, so skip them unless something visible moved
+            let frame = frame_key(app);
+            if needs_redraw
+                || app.timer().is_flashing()
+                || last_frame.as_ref() != Some(&frame)
+                || last_draw.elapsed() >= MAX_REDRAW_INTERVAL
+            {
+                terminal.draw(|f| ui(f, app))?;
+                needs_redraw = false;
+                last_draw = Instant::now();
+                last_frame = Some(frame);
+            }
+        }
+
+        if app
+            .quit_after
+            .is_some_and(|limit| app.timer().stopwatch.elapsed() >= limit)
+        {
+            return Ok(());
+        }
+
+        // Background tabs keep running, so check every timer, not just the one on screen
+        let mut auto_stopped = Vec::new();
+        for (tab, timer) in app.tabs.iter_mut().enumerate() {
+            // Ring the bell once when a countdown reaches zero
+            if timer.stopwatch.countdown_finished() && !timer.countdown_alerted {
+                timer.countdown_alerted = true;
+                ring_bell(app.headless)?;
+                // A Pomodoro goes straight on to its next work or break phase, and an
+                // interval to its next round
+                timer.advance_pomodoro();
+                timer.advance_interval();
+            }
+            if timer.check_alarm() {
+                ring_bell(app.headless)?;
+            }
+            if let Some(limit) = app.auto_stop {
+                if timer.check_auto_stop(limit) {
+                    auto_stopped.push(tab);
+                }
+            }
+            if let Some(interval) = app.milestone_interval {
+                timer.check_milestone(interval);
+            }
+        }
+        for tab in auto_stopped {
+            app.log(tab, "paused");
+        }
+
+        // Wake up in time for the next frame, or handle input as soon as it arrives
+        let poll_rate = if animating {
+            app.tick_rate.saturating_sub(last_frame_tick.elapsed())
+        } else {
+            IDLE_POLL_RATE
+        };
+        let polled_at = Instant::now();
+        let ready = events.poll(poll_rate)?;
+        if app.ignore_suspend {
+            // A wait that took far longer than its timeout means the machine was
+            // asleep, so count at most a couple of ticks of it and drop the rest.
+            // Only the wait is measured, so `Ctrl+Z` and slow work in between count.
+            let max_step = poll_rate.max(app.tick_rate) * 2;
+            let waited = polled_at.elapsed();
+            if waited > max_step {
+                for timer in &mut app.tabs {
+                    timer.stopwatch.skip(waited - max_step);
+                }
+            }
+        }
+        if ready {
+            match events.read()? {
+                // Only handle key press events, not key release or repeat
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    needs_redraw = true;
+
+                    // Raw mode turns Ctrl-C into an ordinary key, so quit on it from anywhere,
+                    // through the same exit path as `q`
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok(());
+                    }
+                    // Wipe anything else that wrote to the terminal; the next frame, drawn
+                    // from scratch, puts every cell back
+                    if key.code == KeyCode::Char('l')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        terminal.clear()?;
+                        continue;
+                    }
+                    // Raw mode also turns Ctrl-Z into a key; suspend as the shell expects
+                    #[cfg(unix)]
+                    if key.code == KeyCode::Char('z')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        if let Some(Suspend { setup, .. }) = &app.suspend {
+                            suspend(terminal, *setup)?;
+                        }
+                        continue;
+                    }
+
+                    let action = app.keys.action(key.code);
+
+                    // The help overlay swallows every key except the ones that close it
+                    if app.show_help {
+                        if action == Some(Action::Help) || key.code == KeyCode::Esc {
+                            app.show_help = false;
+                        }
+                        continue;
+                    }
+
+                    // While a prompt is open, keys edit it instead of driving the stopwatch.
+                    // A command typed at the `:` prompt can hand back an action to run
+                    // as though its key had been pressed
+                    let action = if app.input_mode != InputMode::Normal {
+                        app.handle_input_key(key.code);
+                        match app.command_action.take() {
+                            Some(action) => Some(action),
+                            None => continue,
+                        }
+                    } else {
+                        action
+                    };
+
+                    // A pending reset is confirmed by pressing reset again; anything else cancels it
+                    if app.confirm_reset {
+                        app.confirm_reset = false;
+                        if action == Some(Action::Reset) {
+                            let timer = app.timer_mut();
+                            timer.save_undo("reset");
+                            timer.reset();
+                            app.log(app.active, "reset");
+                        }
+                        continue;
+                    }
+
+                    // Quitting with unexported laps needs a second press; `s` exports
+                    // them instead, and anything else cancels
+                    if app.confirm_quit {
+                        app.confirm_quit = false;
+                        if action == Some(Action::Quit) {
+                            return Ok(());
+                        } else if key.code == KeyCode::Char('s') {
+                            export_laps_file(app, ExportFormat::Csv);
+                        }
+                        continue;
+                    }
+
+                    let Some(action) = action else {
+                        continue;
+                    };
+                    match action {
+                        Action::Quit if app.has_unexported_laps() => app.confirm_quit = true,
+                        Action::Quit => return Ok(()),
+                        Action::Lap => record_lap(app)?,
+                        Action::Pause => {
+                            let stopwatch = &mut app.timer_mut().stopwatch;
+                            // A stopwatch started with --start-paused hasn't begun yet,
+                            // even with an --offset already on the clock
+                            let fresh = !stopwatch.has_started();
+                            stopwatch.toggle_pause();
+                            let running = stopwatch.is_running();
+                            let event = match (running, fresh) {
+                                (true, true) => "started",
+                                (true, false) => "resumed",
+                                (false, _) => "paused",
+                            };
+                            app.log(app.active, event);
+                            // Recorded only once running again, so the pause isn't counted
+                            if running && !fresh && app.lap_on_resume {
+                                record_lap(app)?;
+                            }
+                            // That lap closed the previous segment, so it isn't counted in the new one
+                            if running && !fresh {
+                                let timer = app.timer_mut();
+                                timer.resume_lap_count = timer.stopwatch.timed_laps().len();
+                            }
+                        }
+                        Action::Reset => app.confirm_reset = true,
+                        Action::ResetClock => app.reset_clock(),
+                        Action::DeleteLap => app.timer_mut().delete_selected_lap(),
+                        Action::Export => export_laps_file(app, ExportFormat::Csv),
+                        Action::ExportMarkdown => export_laps_file(app, ExportFormat::Markdown),
+                        Action::CopyLap => copy_selected_lap(app),
+                        Action::CopyAllLaps => copy_all_laps(app),
+                        Action::CopyElapsed => copy_elapsed(app),
+                        Action::CopyCsv => copy_laps_csv(app),
+                        Action::ClearLaps => app.clear_laps(),
+                        Action::Undo => app.undo(),
+                        Action::ToggleStats => app.show_stats = !app.show_stats,
+                        Action::ToggleGraph => app.show_graph = !app.show_graph,
+                        Action::ToggleBigDigits => app.big_digits = !app.big_digits,
+                        Action::ToggleMinimal => app.minimal = !app.minimal,
+                        Action::Freeze => app.toggle_freeze(),
+                        Action::ToggleFormat => {
+                            app.display_format = match app.display_format {
+                                DisplayFormat::Words => DisplayFormat::Clock,
+                                DisplayFormat::Clock => DisplayFormat::Words,
+                            };
+                        }
+                        Action::CycleTheme => {
+                            if app.theme.next_palette() {
+                                let name = app.theme.palette.name;
+                                app.set_status(format!("Theme: {}", name));
+                            } else {
+                                app.set_status("Colors are turned off");
+                            }
+                        }
+                        Action::ToggleBell => {
+                            app.bell_on_lap = !app.bell_on_lap;
+                            let state = if app.bell_on_lap { "on" } else { "off" };
+                            app.set_status(format!("Bell on lap {}", state));
+                        }
+                        Action::ToggleFollow => {
+                            app.auto_follow = !app.auto_follow;
+                            let state = if app.auto_follow { "on" } else { "off" };
+                            app.set_status(format!("Follow new laps {}", state));
+                        }
+                        Action::SetAlarm => app.start_input(InputMode::Alarm, ""),
+                        Action::ExtendCountdown => app.adjust_countdown(true),
+                        Action::ShortenCountdown => app.adjust_countdown(false),
+                        Action::Import => app.start_input(InputMode::ImportPath, ""),
+                        Action::NameSession => {
+                            let name = app.timer().stopwatch.name().unwrap_or_default().to_string();
+                            app.start_input(InputMode::SessionName, &name);
+                        }
+                        Action::LabelLap => match app.timer().selected_lap_index() {
+                            Some(index) => {
+                                let label = app.timer().stopwatch.lap_label(index);
+                                let label = label.unwrap_or_default().to_string();
+                                app.start_input(InputMode::LapLabel, &label);
+                            }
+                            None => app.set_status("No lap selected"),
+                        },
+                        Action::Command => app.start_input(InputMode::Command, ""),
+                        Action::Help => app.show_help = true,
+                        Action::NextTimer => app.next_timer(),
+                        Action::PreviousTimer => app.previous_timer(),
+                        Action::AddTimer => {
+                            app.add_timer();
+                            app.log(app.active, "started");
+                        }
+                        Action::RemoveTimer => app.remove_timer(),
+                        Action::ScrollUp => app.timer_mut().scroll_up(),
+                        Action::ScrollDown => app.timer_mut().scroll_down(),
+                        Action::PageUp => {
+                            let page_size = app.page_size;
+                            app.timer_mut().page_up(page_size)
+                        }
+                        Action::PageDown => {
+                            let page_size = app.page_size;
+                            app.timer_mut().page_down(page_size)
+                        }
+                        Action::FastestLap => app.timer_mut().select_fastest_lap(),
+                        Action::SlowestLap => app.timer_mut().select_slowest_lap(),
+                        Action::SortLaps => app.timer_mut().cycle_lap_sort(),
+                        Action::ReferenceLap => app.toggle_reference_lap(),
+                        Action::NewestLap => app.timer_mut().scroll_to_top(),
+                        Action::OldestLap => app.timer_mut().scroll_to_bottom(),
+                    }
+                }
+                Event::Mouse(mouse) if app.mouse && !app.show_help => match mouse.kind {
+                    MouseEventKind::ScrollUp => {
+                        app.timer_mut().scroll_up();
+                        needs_redraw = true;
+                    }
+                    MouseEventKind::ScrollDown => {
+                        app.timer_mut().scroll_down();
+                        needs_redraw = true;
+                    }
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        app.select_lap_at(mouse.column, mouse.row);
+                        needs_redraw = true;
+                    }
+                    _ => {}
+                },
+                // Redraw straight away rather than waiting for the next key
+                Event::Resize(_, _) => needs_redraw = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Shows `render_summary` until any key is pressed, before the terminal is restored.
+fn show_summary<B: Backend>(
+    terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
+    app: &App,
+) -> io::Result<()> {
+    // Taken once, so the figures hold still while the clock runs on underneath
+    let elapsed = app.timer().stopwatch.elapsed();
+    loop {
+        terminal.draw(|f| render_summary(f, app, elapsed))?;
+        // Anything else, like a resize, just redraws
+        if let Event::Key(key) = events.read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Records a lap on the active timer, ringing the bell and logging it if asked to.
+fn record_lap(app: &mut App) -> io::Result<()> {
+    let follow = app.auto_follow;
+    app.timer_mut().add_lap(follow);
+    if app.bell_on_lap {
+        ring_bell(app.headless)?;
+    }
+    let stopwatch = &app.timer().stopwatch;
+    if let Some(&(_, total)) = stopwatch.laps().last() {
+        let event = format!(
+            "lap {} at {}",
+            stopwatch.laps().len(),
+            format_duration(total, app.precision)
+        );
+        app.log(app.active, &event);
+    }
+    Ok(())
+}
+
+/// Rings the terminal bell, unless there's no terminal to ring it on.
+fn ring_bell(headless: bool) -> io::Result<()> {
+    if headless {
+        return Ok(());
+    }
+    execute!(io::stdout(), Print("\x07"))
+}
+
+/// Joins `hints` and then `essential` with bullets, dropping hints from the end
+/// of `hints` until the line fits in `width` columns. `essential` always stays.
+fn fit_hints(mut hints: Vec<String>, essential: &[String], width: usize) -> String {
+    loop {
+        let line = hints
+            .iter()
+            .chain(essential)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("  •  ");
+        if hints.is_empty() || Span::raw(line.as_str()).width() <= width {
+            return line;
+        }
+        hints.pop();
+    }
+}
+
+/// The figures on the statistics panel, in order.
+fn stats_items(app: &App) -> Vec<String> {
+    let stopwatch = &app.timer().stopwatch;
+    let mut items = lap_stats_items(app);
+    items.push(format!("Active: {}", app.format_time(stopwatch.elapsed())));
+    items.push(format!(
+        "Paused: {}",
+        app.format_time(stopwatch.total_paused())
+    ));
+    if let Some(best) = app.personal_best_text() {
+        items.push(format!("PB: {}", best));
+    }
+    items
+}
+
+/// The statistics panel's figures about the laps themselves.
+fn lap_stats_items(app: &App) -> Vec<String> {
+    let Some(stats) = app.timer().stopwatch.lap_stats() else {
+        return vec!["No laps recorded yet".to_string()];
+    };
+    let mut items = vec![
+        format!("Laps: {}", stats.count),
+        format!(
+            "Average: {} (last {}: {})",
+            app.format_lap_time(stats.avg),
+            app.window,
+            app.format_lap_time(app.recent_avg().unwrap_or_default())
+        ),
+        format!("Best: {}", app.format_lap_time(stats.min)),
+        format!("Worst: {}", app.format_lap_time(stats.max)),
+        format!("Total: {}", app.format_lap_time(stats.total)),
+        // How evenly paced the laps are
+        format!(
+            "σ {} ({})",
+            app.format_lap_time(stats.std_dev),
+            stats
+                .cv
+                .map_or_else(|| "—".to_string(), |cv| format!("{:.0}%", cv * 100.0))
+        ),
+    ];
+    if stats.count > 1 {
+        items.push(format!(
+            "{} above / {} below average",
+            stats.above_avg, stats.below_avg
+        ));
+    }
+    items
+}
+
+/// Joins `items` with bullets into as few lines of at most `width` columns as
+/// it can, never splitting an item. An item wider than `width` gets a line of
+/// its own.
+fn pack_items(items: &[String], width: usize) -> Vec<String> {
+    const SEPARATOR: &str = "  •  ";
+    let mut lines: Vec<String> = Vec::new();
+    for item in items {
+        match lines.last_mut() {
+            Some(line)
+                if line.chars().count() + SEPARATOR.chars().count() + item.chars().count()
+                    <= width =>
+            {
+                line.push_str(SEPARATOR);
+                line.push_str(item);
+            }
+            _ => lines.push(item.clone()),
+        }
+    }
+    lines
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    app.second_bar_cells = 0;
+    if app.minimal {
+        render_minimal(f, app);
+        return;
+    }
+    let palette = app.theme.palette;
+
+    // On short terminals give up the progress gauge, controls, stats and graph before the laps list
+    // gets squeezed to nothing
+    // Statistics that don't fit on one line wrap onto more, between items
+    let stats_lines = if app.show_stats {
+        let width = f.area().width.saturating_sub(2 * MARGIN + 2) as usize;
+        pack_items(&stats_items(app), width)
+    } else {
+        Vec::new()
+    };
+    let stats_height = if app.show_stats {
+        stats_lines.len() as u16 + 2
+    } else {
+        0
+    };
+    let graph_height = if app.show_graph { GRAPH_HEIGHT } else { 0 };
+    let timer_height = if app.big_digits {
+        big_digits::HEIGHT as u16 + 2
+    } else {
+        5
+    };
+    // The tab bar only appears once there's more than one timer to switch between
+    let tabs_height = if app.tabs.len() > 1 { 1 } else { 0 };
+    // Below the title and timer there's nothing worth drawing; everything else can be dropped
+    let min_height = 2 * MARGIN + 3 + tabs_height + timer_height;
+    if f.area().height < min_height {
+        render_too_small(f, app);
+        return;
+    }
+    let gauge_height = match app.timer().stopwatch.mode() {
+        Mode::Countdown { .. } => 3,
+        Mode::Stopwatch => 0,
+    };
+    let full_height = 2 * MARGIN
+        + 3
+        + tabs_height
+        + timer_height
+        + gauge_height
+        + 3
+        + stats_height
+        + graph_height
+        + MIN_LAPS_HEIGHT;
+    let (gauge_height, controls_height, stats_height, graph_height) =
+        if f.area().height < full_height {
+            (0, 0, 0, 0)
+        } else {
+            (gauge_height, 3, stats_height, graph_height)
+        };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(MARGIN)
+        .constraints([
+            Constraint::Length(3),               // Title
+            Constraint::Length(tabs_height),     // Timer tabs
+            Constraint::Length(timer_height),    // Main timer
+            Constraint::Length(gauge_height),    // Countdown progress
+            Constraint::Length(controls_height), // Status/controls
+            Constraint::Length(stats_height),    // Lap statistics
+            Constraint::Length(graph_height),    // Lap time sparkline
+            Constraint::Min(0),                  // Laps list
+        ])
+        .split(f.area());
+
+    // Title
+    let mut title_text = match app.timer().stopwatch.name() {
+        Some(name) => format!("⏱️  {}", name),
+        None => "⏱️  STOPWATCH".to_string(),
+    };
+    if let Some(pomodoro) = &app.timer().pomodoro {
+        let phase = match pomodoro.phase() {
+            Phase::Work => format!("Work {}/{}", pomodoro.cycle(), pomodoro.long_break_every),
+            Phase::ShortBreak => "Break".to_string(),
+            Phase::LongBreak => "Long break".to_string(),
+        };
+        title_text = format!("{}  •  {}", title_text, phase);
+    }
+    if let Some(interval) = &app.timer().interval {
+        let round = match interval.rounds {
+            Some(rounds) => format!("Round {}/{}", interval.round(), rounds),
+            None => format!("Round {}", interval.round()),
+        };
+        let state = if interval.is_done() { " done" } else { "" };
+        title_text = format!("{}  •  {}{}", title_text, round, state);
+    }
+    let title = Paragraph::new(title_text)
+        .style(
+            Style::default()
+                .fg(palette.title)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    " Started {} ",
+                    app.timer().started_at.format(CLOCK_FORMAT)
+                ))
+                .title(
+                    Line::from(format!(" Now {} ", Local::now().format(CLOCK_FORMAT)))
+                        .right_aligned(),
+                )
+                .title_style(Style::default().fg(palette.muted)),
+        );
+    f.render_widget(title, chunks[0]);
+
+    // Timer tabs
+    if app.tabs.len() > 1 {
+        let titles = app.tabs.iter().enumerate().map(|(i, timer)| {
+            let name = timer.stopwatch.name().unwrap_or("Timer");
+            let state = if timer.stopwatch.is_running() {
+                ""
+            } else {
+                " ⏸"
+            };
+            format!("{} {}{}", i + 1, name, state)
+        });
+        let tabs = Tabs::new(titles)
+            .select(app.active)
+            .style(Style::default().fg(palette.muted))
+            .highlight_style(
+                Style::default()
+                    .fg(palette.title)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            );
+        f.render_widget(tabs, chunks[1]);
+    }
+
+    // Main timer display
+    let elapsed = app.timer().stopwatch.elapsed();
+    let elapsed_str = app.format_time(app.timer().shown_time());
+
+    let is_running = app.timer().stopwatch.is_running();
+    let status_indicator = if is_running { "⏸" } else { "▶" };
+    let timer_style = timer_style(app.timer(), &app.theme);
+
+    // Big digits only when they fit inside the border; otherwise fall back to plain text
+    let fits_big = big_digits::width(&elapsed_str) + 2 <= usize::from(chunks[2].width)
+        && usize::from(chunks[2].height) >= big_digits::HEIGHT + 2;
+    let timer_text = if app.big_digits && fits_big {
+        big_digits(&elapsed_str)
+            .into_iter()
+            .map(|line| line.style(timer_style))
+            .collect()
+    } else {
+        let mut indicator_style = Style::default().fg(palette.text);
+        if !is_running {
+            indicator_style = indicator_style.add_modifier(Modifier::SLOW_BLINK);
+        }
+        vec![Line::from(vec![
+            Span::styled(status_indicator, indicator_style),
+            Span::raw("  "),
+            Span::styled(elapsed_str, timer_style),
+        ])]
+    };
+
+    let timer_title = match app.timer().stopwatch.mode() {
+        Mode::Stopwatch => "Elapsed Time",
+        Mode::Countdown { .. } => "Remaining Time",
+    };
+
+    let mut markers = Vec::new();
+    if !is_running {
+        markers.push("PAUSED");
+    }
+    if app.timer().frozen.is_some() {
+        markers.push("FROZEN");
+    }
+    let timer_title = if markers.is_empty() {
+        timer_title.to_string()
+    } else {
+        format!("{} ({})", timer_title, markers.join(", "))
+    };
+
+    let mut timer_block = Block::default().borders(Borders::ALL).title(timer_title);
+    // Dim the whole panel while paused so it's obvious at a glance; a flash still wins
+    if !is_running {
+        timer_block = timer_block.border_style(Style::default().fg(palette.dim));
+    }
+    if app.timer().border_flash_on() {
+        timer_block = timer_block.border_style(Style::default().fg(palette.bad));
+    }
+    // Live pacing feedback, on the border so it fits under big digits too
+    if let Some(stats) = app.timer().stopwatch.lap_stats() {
+        timer_block = timer_block.title_bottom(
+            Line::from(format!(" Avg lap {} ", app.format_lap_time(stats.avg)))
+                .centered()
+                .style(Style::default().fg(palette.muted)),
+        );
+    }
+
+    let timer_inner = timer_block.inner(chunks[2]);
+    let timer_lines = timer_text.len();
+    let timer = Paragraph::new(timer_text)
+        .alignment(Alignment::Center)
+        .block(timer_block);
+    f.render_widget(timer, chunks[2]);
+
+    // Sub-second feedback on the timer's bottom row, when the time leaves it free
+    let second_bar_ratio = app.timer().second_bar_ratio();
+    if let Some(ratio) =
+        second_bar_ratio.filter(|_| app.second_bar && usize::from(timer_inner.height) > timer_lines)
+    {
+        // The line starts one cell in from the empty label, so only trim the right
+        let bar_area = Rect {
+            y: timer_inner.bottom() - 1,
+            height: 1,
+            width: timer_inner.width.saturating_sub(1),
+            ..timer_inner
+        };
+        app.second_bar_cells = bar_area.width.saturating_sub(1);
+        let second_bar = LineGauge::default()
+            .ratio(ratio)
+            .label("")
+            .filled_style(
+                Style::default()
+                    .fg(palette.title)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .unfilled_style(Style::default().fg(palette.dim));
+        f.render_widget(second_bar, bar_area);
+    }
+
+    // Countdown progress, filling up as the target approaches
+    let countdown_target = match app.timer().stopwatch.mode() {
+        Mode::Countdown { target } if gauge_height > 0 => Some(target),
+        _ => None,
+    };
+    if let Some(target) = countdown_target {
+        // A frozen display holds the time left, so hold the progress with it
+        let elapsed = app
+            .timer()
+            .frozen
+            .map_or(elapsed, |left| target.saturating_sub(left));
+        let ratio = if elapsed >= target {
+            1.0
+        } else {
+            elapsed.as_secs_f64() / target.as_secs_f64()
+        };
+        let gauge_color = if ratio < 0.5 {
+            palette.good
+        } else if ratio < 0.8 {
+            palette.warn
+        } else {
+            palette.bad
+        };
+        // Round down so 100% only shows once the target is actually reached
+        let gauge = Gauge::default()
+            .ratio(ratio)
+            .label(format!("{}%", (ratio * 100.0).floor()))
+            .gauge_style(Style::default().fg(gauge_color))
+            .block(Block::default().borders(Borders::ALL).title("Progress"));
+        f.render_widget(gauge, chunks[3]);
+    }
+
+    // Controls, or a prompt or transient status message in their place
+    if let Some(prompt) = app.input_mode.prompt() {
+        let input = Paragraph::new(format!("{}{}", prompt, app.input_buffer))
+            .style(Style::default().fg(palette.text))
+            .block(Block::default().borders(Borders::ALL).title("Input"));
+        f.render_widget(input, chunks[4]);
+
+        // Put the terminal cursor where the next character will be typed
+        let before_cursor = &app.input_buffer[..app.input_byte_index()];
+        let cursor_x = Span::raw(prompt).width() + Span::raw(before_cursor).width();
+        if chunks[4].height > 2 {
+            f.set_cursor_position(Position {
+                x: (chunks[4].x + 1 + cursor_x as u16).min(chunks[4].right().saturating_sub(2)),
+                y: chunks[4].y + 1,
+            });
+        }
+    } else {
+        let key = |action| app.keys.short_label(action);
+        let (controls, controls_color) = if let Some(notice) = notice(app) {
+            notice
+        } else {
+            let mut hints = Vec::new();
+            if app.timer().stopwatch.is_running() {
+                hints.push(format!("{}: Lap", key(Action::Lap)));
+                hints.push(format!("{}: Pause", key(Action::Pause)));
+            } else {
+                hints.push(format!("{}: Resume", key(Action::Pause)));
+            }
+            hints.push(format!("{}: Reset", key(Action::Reset)));
+            hints.push(format!(
+                "{}{}: Scroll",
+                key(Action::ScrollUp),
+                key(Action::ScrollDown)
+            ));
+            let essential = [
+                format!("{}: Help", key(Action::Help)),
+                format!("{}: Quit", key(Action::Quit)),
+            ];
+            let width = usize::from(chunks[4].width.saturating_sub(2));
+            (fit_hints(hints, &essential, width), palette.muted)
+        };
+
+        let mut controls_block = Block::default().borders(Borders::ALL).title("Controls");
+        // Counting down to the alarm; a countdown's own target is already the main display
+        if let Some(left) = app.timer().until_alarm() {
+            controls_block = controls_block
+                .title(Line::from(format!(" Alarm in {} ", app.format_time(left))).right_aligned());
+        }
+        let controls_widget = Paragraph::new(controls)
+            .style(Style::default().fg(controls_color))
+            .alignment(Alignment::Center)
+            .block(controls_block);
+        f.render_widget(controls_widget, chunks[4]);
+    }
+
+    // Lap statistics
+    if app.show_stats {
+        let mut stats_block = Block::default()
+            .borders(Borders::ALL)
+            .title("Statistics")
+            .title(format!(" Segment laps: {} ", app.timer().segment_laps()));
+        if let Some(rate) = app.timer().lap_rate() {
+            stats_block =
+                stats_block.title(Line::from(format!("{:.1} laps/min", rate)).right_aligned());
+        }
+        let stats_text: Vec<Line> = stats_lines.into_iter().map(Line::from).collect();
+        let stats_widget = Paragraph::new(stats_text)
+            .alignment(Alignment::Center)
+            .block(stats_block);
+        f.render_widget(stats_widget, chunks[5]);
+    }
+
+    // Lap time sparkline, oldest lap on the left
+    if app.show_graph {
+        let graph_block = Block::default().borders(Borders::ALL).title("Lap Times");
+        let laps = app.timer().stopwatch.timed_laps();
+        if laps.len() < 2 {
+            let placeholder = Paragraph::new("Record two laps to see the graph")
+                .style(Style::default().fg(palette.muted))
+                .alignment(Alignment::Center)
+                .block(graph_block);
+            f.render_widget(placeholder, chunks[6]);
+        } else {
+            let data: Vec<u64> = laps
+                .iter()
+                .map(|(lap_time, _)| lap_time.as_millis() as u64)
+                .collect();
+            let sparkline = Sparkline::default()
+                .data(&data)
+                .style(Style::default().fg(palette.title))
+                .block(graph_block);
+            f.render_widget(sparkline, chunks[6]);
+        }
+    }
+
+    // Laps list
+    let lap_count = app.timer().stopwatch.laps().len();
+    if lap_count > 0 {
+        let rows = lap_rows(
+            &app.timer().stopwatch,
+            app.lap_precision,
+            app.display_format,
+            &app.theme.palette,
+        );
+        let widths = column_widths(&rows);
+        // An extra column of deltas against the reference lap, once one is picked
+        let reference = app.timer().reference_lap.map(|reference| {
+            let header = format!("vs Lap {}", reference + 1);
+            let cells = reference_cells(
+                &app.timer().stopwatch,
+                reference,
+                app.lap_precision,
+                &palette,
+            );
+            let width = cells
+                .iter()
+                .map(|(cell, _)| cell.chars().count())
+                .fold(header.chars().count(), usize::max);
+            (header, cells, width)
+        });
+
+        let list_rows = app.timer().list_rows();
+        let laps: Vec<ListItem> = list_rows
+            .iter()
+            .enumerate()
+            .map(|(item, &lap)| {
+                let Some(i) = lap else {
+                    // Headings come right before the phase's newest lap
+                    let phase =
+                        list_rows[item + 1].map_or(0, |next| app.timer().stopwatch.lap_phase(next));
+                    return ListItem::new(Line::styled(
+                        format!("Phase {}", phase + 1),
+                        Style::default()
+                            .fg(palette.title)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                };
+                let row = &rows[i];
+                let [lap, lap_time, delta, split] = pad_cells(row.cells(), widths);
+                let delta_style = row
+                    .delta
+                    .as_ref()
+                    .map_or(Style::new().fg(palette.muted), |&(_, style)| style);
+                let mut spans = vec![
+                    Span::styled(lap, Style::default().fg(palette.accent)),
+                    Span::raw(COLUMN_GAP),
+                    Span::styled(lap_time, row.lap_style),
+                    Span::raw(COLUMN_GAP),
+                    Span::styled(delta, delta_style),
+                    Span::raw(COLUMN_GAP),
+                    Span::styled(split, Style::default().fg(palette.muted)),
+                ];
+                if let Some((_, cells, width)) = &reference {
+                    let (cell, style) = &cells[i];
+                    spans.push(Span::raw(COLUMN_GAP));
+                    spans.push(Span::styled(format!("{:>width$}", cell), *style));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let sort = match app.timer().lap_sort {
+            LapSort::Chronological => String::new(),
+            LapSort::Fastest => ", fastest first".to_string(),
+            LapSort::Slowest => ", slowest first".to_string(),
+        };
+        let mut laps_block = Block::default().borders(Borders::ALL).title(format!(
+            "Laps ({}{}) - Use {}{} to scroll",
+            lap_count,
+            sort,
+            app.keys.short_label(Action::ScrollUp),
+            app.keys.short_label(Action::ScrollDown)
+        ));
+        // A quick total without opening the statistics panel
+        if let Some(stats) = app.timer().stopwatch.lap_stats() {
+            laps_block = laps_block.title_bottom(
+                Line::from(format!(
+                    " Total {}  •  Avg {} ",
+                    app.format_lap_time(stats.total),
+                    app.format_lap_time(stats.avg)
+                ))
+                .right_aligned()
+                .style(Style::default().fg(palette.muted)),
+            );
+        }
+        let laps_inner = laps_block.inner(chunks[7]);
+        f.render_widget(laps_block, chunks[7]);
+
+        // Column headings take the first row inside the border; the laps fill the rest
+        let [header_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(laps_inner);
+        // The marker gets a gutter of its own, so the headings move over with the laps
+        let marker = if app.theme.selection_marker() {
+            SELECTION_MARKER
+        } else {
+            ""
+        };
+        let mut header = " ".repeat(marker.chars().count());
+        header.push_str(&pad_cells(LAP_HEADERS, widths).join(COLUMN_GAP));
+        if let Some((reference_header, _, width)) = &reference {
+            header.push_str(COLUMN_GAP);
+            header.push_str(&format!("{:>width$}", reference_header));
+        }
+        f.render_widget(
+            Paragraph::new(header).style(
+                Style::default()
+                    .fg(palette.muted)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            header_area,
+        );
+        app.laps_area = list_area;
+
+        let laps_widget = List::new(laps)
+            .highlight_style(palette.highlight)
+            .highlight_symbol(marker)
+            .highlight_spacing(HighlightSpacing::Always);
+
+        // The selection counts laps only, so point it past any headings for drawing.
+        // The scroll offset is kept in list rows, headings included.
+        let laps_list_state = &mut app.timer_mut().laps_list_state;
+        let selected_lap = laps_list_state.selected();
+        let selected_row = selected_lap.and_then(|selected| {
+            list_rows
+                .iter()
+                .enumerate()
+                .filter(|(_, lap)| lap.is_some())
+                .nth(selected)
+                .map(|(row, _)| row)
+        });
+        laps_list_state.select(selected_row);
+        f.render_stateful_widget(laps_widget, list_area, laps_list_state);
+        laps_list_state.select(selected_lap);
+
+        // Scrollbar over the right border beside the rows, only once they overflow
+        if list_rows.len() > usize::from(list_area.height) {
+            let mut scrollbar_state =
+                ScrollbarState::new(list_rows.len()).position(selected_row.unwrap_or(0));
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None);
+            let scrollbar_area = Rect {
+                y: list_area.y,
+                height: list_area.height,
+                ..chunks[7]
+            };
+            f.render_stateful_widget(scrollbar, scrollbar_area, &mut scrollbar_state);
+        }
+    } else {
+        app.laps_area = Rect::default();
+        let no_laps = Paragraph::new(format!(
+            "Press {} to record your first lap!",
+            app.keys.short_label(Action::Lap)
+        ))
+        .style(Style::default().fg(palette.muted))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL).title("Laps"));
+        f.render_widget(no_laps, chunks[7]);
+    }
+
+    if app.show_help {
+        render_help(f, &app.keys, app.mouse, &app.theme.palette);
+    }
+}
+
+/// Column headings of the laps table.
+const LAP_HEADERS: [&str; 4] = ["Lap", "Time", "Δ", "Split"];
+
+/// One lap's cells in the laps table, before padding.
+struct LapRow {
+    lap: String,
+    lap_time: String,
+    lap_style: Style,
+    delta: Option<(String, Style)>,
+    split: String,
+}
+
+impl LapRow {
+    fn cells(&self) -> [&str; 4] {
+        let delta = self.delta.as_ref().map_or("", |(delta, _)| delta.as_str());
+        [&self.lap, &self.lap_time, delta, &self.split]
+    }
+}
+
+/// Formats every lap, oldest first, so each column can be padded to its widest entry.
+/// Deltas are always in words, which stay short for small differences.
+fn lap_rows(
+    stopwatch: &Stopwatch,
+    precision: Precision,
+    format: DisplayFormat,
+    palette: &Palette,
+) -> Vec<LapRow> {
+    let laps = stopwatch.laps();
+    // Fastest and slowest lap times; only meaningful once there's something to
+    // compare, which a start lap isn't
+    let timed = stopwatch.timed_laps();
+    let first_timed = laps.len() - timed.len();
+    let lap_times = timed.iter().map(|(lap_time, _)| *lap_time);
+    let fastest = lap_times.clone().min().filter(|_| timed.len() > 1);
+    let slowest = lap_times.max().filter(|_| timed.len() > 1);
+
+    laps.iter()
+        .enumerate()
+        .map(|(i, &(lap_time, total_time))| LapRow {
+            lap: match stopwatch.lap_label(i) {
+                Some(label) => format!("Lap {} · {}", i + 1, label),
+                None => format!("Lap {}", i + 1),
+            },
+            lap_time: format.format(lap_time, precision),
+            lap_style: if i < first_timed {
+                Style::new().fg(palette.muted)
+            } else if Some(lap_time) == fastest {
+                palette.good_style()
+            } else if Some(lap_time) == slowest {
+                palette.bad_style()
+            } else {
+                Style::new().fg(palette.text)
+            },
+            // The first timed lap has nothing to compare against
+            delta: i
+                .checked_sub(1)
+                .filter(|&prev| prev >= first_timed)
+                .map(|prev| lap_delta(lap_time, laps[prev].0, precision, palette)),
+            split: format.format(total_time, precision),
+        })
+        .collect()
+}
+
+/// Width of each column: its widest cell, or the heading if that's wider.
+fn column_widths(rows: &[LapRow]) -> [usize; 4] {
+    let mut widths = LAP_HEADERS.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.cells()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    widths
+}
+
+/// Pads a row to `widths`: the lap label left-aligned, the durations right-aligned.
+fn pad_cells(cells: [&str; 4], widths: [usize; 4]) -> [String; 4] {
+    std::array::from_fn(|i| {
+        let (cell, width) = (cells[i], widths[i]);
+        if i == 0 {
+            format!("{:<width$}", cell)
+        } else {
+            format!("{:>width$}", cell)
+        }
+    })
+}
+
+/// `+1.20s` in the bad color for a slower lap, `-0.80s` in the good one for a faster one.
+fn lap_delta(
+    lap_time: Duration,
+    previous: Duration,
+    precision: Precision,
+    palette: &Palette,
+) -> (String, Style) {
+    let (sign, delta, style) = if lap_time > previous {
+        ("+", lap_time - previous, palette.bad_style())
+    } else if lap_time < previous {
+        ("-", previous - lap_time, palette.good_style())
+    } else {
+        ("±", Duration::ZERO, Style::new().fg(palette.muted))
+    };
+    (
+        format!("{}{}", sign, format_duration(delta, precision)),
+        style,
+    )
+}
+
+/// Each lap's delta against the lap at `reference`, which itself shows `ref`.
+fn reference_cells(
+    stopwatch: &Stopwatch,
+    reference: usize,
+    precision: Precision,
+    palette: &Palette,
+) -> Vec<(String, Style)> {
+    let laps = stopwatch.laps();
+    laps.iter()
+        .enumerate()
+        .map(|(i, &(lap_time, _))| {
+            if i == reference {
+                ("ref".to_string(), Style::new().fg(palette.muted))
+            } else {
+                lap_delta(lap_time, laps[reference].0, precision, palette)
+            }
+        })
+        .collect()
+}
+
+/// Just the time, centered, plus a line for any prompt or message. Laps and
+/// everything else are hidden but keys still work on them.
+fn render_minimal(f: &mut Frame, app: &mut App) {
+    // Nothing to click on
+    app.laps_area = Rect::default();
+
+    let area = f.area();
+    let time = app.format_time(app.timer().shown_time());
+    let style = timer_style(app.timer(), &app.theme);
+    let fits_big = big_digits::width(&time) <= usize::from(area.width)
+        && usize::from(area.height) > big_digits::HEIGHT;
+    let mut lines: Vec<Line> = if app.big_digits && fits_big {
+        big_digits(&time)
+            .into_iter()
+            .map(|line| line.style(style))
+            .collect()
+    } else {
+        vec![Line::styled(time, style)]
+    };
+    if let Some(prompt) = app.input_mode.prompt() {
+        lines.push(Line::styled(
+            format!("{}{}", prompt, app.input_buffer),
+            Style::default().fg(app.theme.palette.text),
+        ));
+    } else if let Some((notice, color)) = notice(app) {
+        lines.push(Line::styled(notice, Style::default().fg(color)));
+    }
+
+    let height = (lines.len() as u16).min(area.height);
+    let top = Rect {
+        y: area.y + (area.height - height) / 2,
+        height,
+        ..area
+    };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), top);
+
+    if app.show_help {
+        render_help(f, &app.keys, app.mouse, &app.theme.palette);
+    }
+}
+
+/// Stands in for the full UI when the terminal is too short for the title and timer.
+fn render_too_small(f: &mut Frame, app: &mut App) {
+    // Nothing to click on
+    app.laps_area = Rect::default();
+
+    let area = f.area();
+    let message = format!(
+        "Window too small ({}: time only)",
+        app.keys.short_label(Action::ToggleMinimal)
+    );
+    let middle = Rect {
+        y: area.y + area.height / 2,
+        height: area.height.min(1),
+        ..area
+    };
+    f.render_widget(
+        Paragraph::new(message)
+            .style(Style::default().fg(app.theme.palette.warn))
+            .alignment(Alignment::Center),
+        middle,
+    );
+}
+
+/// A pending confirmation or transient status message, shown in place of the controls.
+fn notice(app: &App) -> Option<(String, Color)> {
+    let key = |action| app.keys.short_label(action);
+    let palette = &app.theme.palette;
+    if app.confirm_reset {
+        Some((
+            format!(
+                "Press {} again to confirm reset, any other key to cancel",
+                key(Action::Reset)
+            ),
+            palette.bad,
+        ))
+    } else if app.confirm_quit {
+        Some((
+            format!(
+                "Unsaved laps — press {} again to quit, s to save",
+                key(Action::Quit)
+            ),
+            palette.bad,
+        ))
+    } else {
+        app.status_message()
+            .map(|message| (message.to_string(), palette.warn))
+    }
+}
+
+/// What a tick can change on screen by itself: the shown time, the lap count, the
+/// timer's color and how many cells of the second bar are filled. Frames with the
+/// same key look the same.
+fn frame_key(app: &App) -> (String, usize, Style, Option<u16>) {
+    let timer = app.timer();
+    let second_bar = timer
+        .second_bar_ratio()
+        .map(|ratio| (ratio * f64::from(app.second_bar_cells)) as u16);
+    (
+        app.format_time(timer.shown_time()),
+        timer.stopwatch.laps().len(),
+        timer_style(timer, &app.theme),
+        second_bar,
+    )
+}
+
+/// Color and emphasis of the main time: the theme's color for the elapsed
+/// time, the bad color once a countdown is over, and reversed while flashing.
+fn timer_style(timer: &TimerState, theme: &Theme) -> Style {
+    let elapsed = timer.stopwatch.elapsed();
+    let color = if timer.stopwatch.countdown_finished() {
+        theme.palette.bad
+    } else {
+        theme.timer_color(elapsed)
+    };
+
+    let mut style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+    if let Mode::Countdown { target } = timer.stopwatch.mode() {
+        // Flash twice a second once the countdown has run out
+        if elapsed >= target && ((elapsed - target).as_millis() / 500).is_multiple_of(2) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+    }
+    if timer.milestone_flash_on() {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+}
+
+fn render_help(f: &mut Frame, keys: &KeyBindings, mouse: bool, palette: &Palette) {
+    let entries: Vec<(String, &str)> = KEY_HELP
+        .iter()
+        .map(|(actions, description)| {
+            let labels: Vec<String> = actions.iter().map(|&action| keys.label(action)).collect();
+            (labels.join(" / "), *description)
+        })
+        .chain(
+            FIXED_KEY_HELP
+                .iter()
+                .filter(|(key, _)| mouse || *key != "Mouse")
+                .map(|(key, description)| (key.to_string(), *description)),
+        )
+        .collect();
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = entries
+        .iter()
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>width$}", key, width = key_width),
+                    Style::default()
+                        .fg(palette.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::raw(*action),
+            ])
+        })
+        .collect();
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let area = centered_rect(width, height, f.area());
+
+    let help = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+        "Help - press {} or ESC to close",
+        keys.short_label(Action::Help)
+    )));
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+/// The active timer's final figures, for `--summary`: its name, total time and
+/// lap statistics.
+fn render_summary(f: &mut Frame, app: &App, elapsed: Duration) {
+    let palette = &app.theme.palette;
+    let stopwatch = &app.timer().stopwatch;
+    let mut rows = Vec::new();
+    if let Some(name) = stopwatch.name() {
+        rows.push(("Session", name.to_string()));
+    }
+    rows.push(("Total time", app.format_time(elapsed)));
+    rows.push(("Laps", stopwatch.timed_laps().len().to_string()));
+    if let Some(stats) = stopwatch.lap_stats() {
+        rows.push(("Average", app.format_lap_time(stats.avg)));
+        rows.push(("Best", app.format_lap_time(stats.min)));
+        rows.push(("Worst", app.format_lap_time(stats.max)));
+    }
+
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>label_width$}", label),
+                    Style::default()
+                        .fg(palette.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(value, Style::default().fg(palette.text)),
+            ])
+        })
+        .collect();
+
+    let title = "Summary - press any key to exit";
+    let width = lines
+        .iter()
+        .map(Line::width)
+        .max()
+        .unwrap_or(0)
+        .max(title.chars().count()) as u16
+        + 4;
+    let height = lines.len() as u16 + 2;
+    let area = centered_rect(width, height, f.area());
+
+    let summary = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(palette.title)),
+    );
+    f.render_widget(summary, area);
+}
+
+/// A `width` x `height` rectangle centered in `area`, shrunk to fit if needed.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+
+    /// A paused timer with laps of `secs` each, so its clock reads their sum.
+    fn timer_with_laps(secs: &[u64]) -> TimerState {
+        let mut total = Duration::ZERO;
+        let laps = secs
+            .iter()
+            .map(|&secs| {
+                total += Duration::from_secs(secs);
+                (Duration::from_secs(secs), total)
+            })
+            .collect();
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.replace_laps(laps).unwrap();
+        TimerState::new(stopwatch)
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    /// Replays queued events to `run_app`, then reports nothing for `idle` and
+    /// quits with Ctrl+C, like `--commands` at the end of its input.
+    struct MockEventSource {
+        events: VecDeque<Event>,
+        idle: Duration,
+        idle_since: Option<Instant>,
+        stall: Duration, // How long the first poll blocks, whatever its timeout
+    }
+
+    impl MockEventSource {
+        fn new(events: Vec<Event>) -> MockEventSource {
+            MockEventSource {
+                events: events.into(),
+                idle: Duration::ZERO,
+                idle_since: None,
+                stall: Duration::ZERO,
+            }
+        }
+
+        /// A press of each character in `keys`, in order.
+        fn keys(keys: &str) -> MockEventSource {
+            let events = keys
+                .chars()
+                .map(|c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)))
+                .collect();
+            MockEventSource::new(events)
+        }
+
+        fn idle_for(mut self, idle: Duration) -> MockEventSource {
+            self.idle = idle;
+            self
+        }
+
+        /// Makes the first poll overrun its timeout by blocking for `stall`, as it
+        /// does when the machine sleeps.
+        fn stall_for(mut self, stall: Duration) -> MockEventSource {
+            self.stall = stall;
+            self
+        }
+    }
+
+    impl EventSource for MockEventSource {
+        fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+            if !self.stall.is_zero() {
+                thread::sleep(mem::take(&mut self.stall));
+                return Ok(false);
+            }
+            if !self.events.is_empty() {
+                return Ok(true);
+            }
+            let idle_since = *self.idle_since.get_or_insert_with(Instant::now);
+            let left = self.idle.saturating_sub(idle_since.elapsed());
+            if left.is_zero() {
+                let quit = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+                self.events.push_back(Event::Key(quit));
+                return Ok(true);
+            }
+            thread::sleep(timeout.min(left));
+            Ok(false)
+        }
+
+        fn read(&mut self) -> io::Result<Event> {
+            while self.events.is_empty() {
+                self.poll(self.idle)?;
+            }
+            Ok(self.events.pop_front().expect("just checked"))
+        }
+    }
+
+    /// An app around `stopwatch` that never touches the real terminal.
+    fn test_app(stopwatch: Stopwatch) -> App {
+        let mut app = App::new(vec![stopwatch]);
+        app.headless = true;
+        app
+    }
+
+    /// Runs `app` on a `TestBackend` until `events` run out, returning how many
+    /// frames were drawn.
+    fn run(app: &mut App, events: MockEventSource) -> usize {
+        run_sized(app, events, 100, 40)
+    }
+
+    /// `run` on a terminal of `width` by `height`.
+    fn run_sized(app: &mut App, mut events: MockEventSource, width: u16, height: u16) -> usize {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        run_app(&mut terminal, &mut events, app).unwrap();
+        terminal.get_frame().count()
+    }
+
+    #[test]
+    fn deleting_the_middle_lap_keeps_the_totals() {
+        let mut timer = timer_with_laps(&[1, 2, 3]);
+        // Newest first, so the middle row is Lap 2
+        timer.laps_list_state.select(Some(1));
+        timer.delete_selected_lap();
+
+        // Lap 3 takes over Lap 2's time, and both totals stay as they were
+        assert_eq!(
+            timer.stopwatch.laps(),
+            [(secs(1), secs(1)), (secs(5), secs(6))]
+        );
+        assert_eq!(timer.laps_list_state.selected(), Some(1));
+        assert_eq!(timer.stopwatch.elapsed(), secs(6));
+    }
+
+    #[test]
+    fn deleting_the_last_row_moves_the_selection_up() {
+        let mut timer = timer_with_laps(&[1, 2, 3]);
+        timer.laps_list_state.select(Some(2));
+        timer.delete_selected_lap();
+        assert_eq!(timer.stopwatch.laps().len(), 2);
+        assert_eq!(timer.laps_list_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn deleting_without_a_selection_does_nothing() {
+        let mut timer = timer_with_laps(&[1, 2, 3]);
+        timer.laps_list_state.select(None);
+        timer.delete_selected_lap();
+        assert_eq!(timer.stopwatch.laps().len(), 3);
+
+        let mut timer = timer_with_laps(&[]);
+        timer.delete_selected_lap();
+        assert!(timer.stopwatch.laps().is_empty());
+    }
+
+    #[test]
+    fn keys_drive_the_event_loop() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("  p"));
+        assert_eq!(app.timer().stopwatch.laps().len(), 2);
+        assert!(!app.timer().stopwatch.is_running());
+        assert_eq!(app.timer().laps_list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn a_reset_needs_confirming() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("  r"));
+        assert!(app.confirm_reset);
+        assert_eq!(app.timer().stopwatch.laps().len(), 2);
+
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("  rr"));
+        assert!(app.timer().stopwatch.laps().is_empty());
+    }
+
+    #[test]
+    fn ticks_carry_on_when_no_keys_arrive() {
+        let mut app = test_app(Stopwatch::new(Mode::Countdown {
+            target: Duration::from_millis(50),
+        }));
+        run(
+            &mut app,
+            MockEventSource::new(Vec::new()).idle_for(Duration::from_millis(200)),
+        );
+        assert!(app.timer().stopwatch.countdown_finished());
+        assert!(app.timer().countdown_alerted);
+    }
+
+    #[test]
+    fn shift_b_toggles_the_bell_on_lap() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("B"));
+        assert!(app.bell_on_lap);
+        assert_eq!(app.status_message(), Some("Bell on lap on"));
+
+        run(&mut app, MockEventSource::keys("B "));
+        assert!(!app.bell_on_lap);
+        assert_eq!(app.timer().stopwatch.laps().len(), 1);
+    }
+
+    #[test]
+    fn lap_rate_is_laps_per_minute_of_elapsed() {
+        // Three laps in 90 seconds
+        let timer = timer_with_laps(&[20, 30, 40]);
+        assert_eq!(timer.stopwatch.elapsed(), secs(90));
+        assert_eq!(timer.lap_rate(), Some(2.0));
+    }
+
+    #[test]
+    fn no_lap_rate_without_laps_or_time() {
+        assert_eq!(timer_with_laps(&[]).lap_rate(), None);
+        assert_eq!(timer_with_laps(&[0]).lap_rate(), None);
+    }
+
+    #[test]
+    fn clearing_laps_leaves_the_clock_alone() {
+        let mut app = App::new(vec![timer_with_laps(&[1, 2, 3]).stopwatch]);
+        app.clear_laps();
+        let timer = app.timer();
+        assert!(timer.stopwatch.laps().is_empty());
+        assert_eq!(timer.stopwatch.elapsed(), secs(6));
+        assert!(!timer.stopwatch.is_running());
+        assert_eq!(timer.laps_list_state.selected(), None);
+
+        let mut app = App::new(vec![Stopwatch::new(Mode::Stopwatch)]);
+        app.timer_mut().stopwatch.add_lap();
+        let before = app.timer().stopwatch.elapsed();
+        app.clear_laps();
+        assert!(app.timer().stopwatch.is_running());
+        assert!(app.timer().stopwatch.elapsed() >= before);
+    }
+
+    #[test]
+    fn undo_brings_back_a_deleted_lap() {
+        let mut timer = timer_with_laps(&[1, 2, 3]);
+        timer.laps_list_state.select(Some(1));
+        timer.delete_selected_lap();
+        assert_eq!(timer.undo(), Some("lap deletion"));
+        assert_eq!(
+            timer.stopwatch.laps(),
+            [(secs(1), secs(1)), (secs(2), secs(3)), (secs(3), secs(6))]
+        );
+        assert_eq!(timer.stopwatch.elapsed(), secs(6));
+        assert_eq!(timer.undo(), None);
+    }
+
+    #[test]
+    fn undo_history_keeps_the_latest_snapshots() {
+        let mut timer = timer_with_laps(&[1; 12]);
+        for _ in 0..12 {
+            timer.laps_list_state.select(Some(0));
+            timer.delete_selected_lap();
+        }
+        assert!(timer.stopwatch.laps().is_empty());
+        assert_eq!(timer.history.len(), UNDO_LIMIT);
+        while timer.undo().is_some() {}
+        // The two oldest deletions fell off the end
+        assert_eq!(timer.stopwatch.laps().len(), UNDO_LIMIT);
+    }
+
+    #[test]
+    fn jumps_to_the_fastest_lap_first_of_any_tie() {
+        let mut timer = timer_with_laps(&[3, 1, 2, 1]);
+        timer.select_fastest_lap();
+        assert_eq!(timer.selected_lap_index(), Some(1));
+    }
+
+    #[test]
+    fn jumps_to_the_slowest_lap_first_of_any_tie() {
+        let mut timer = timer_with_laps(&[2, 5, 1, 5]);
+        timer.select_slowest_lap();
+        assert_eq!(timer.selected_lap_index(), Some(1));
+    }
+
+    #[test]
+    fn fastest_and_slowest_do_nothing_without_laps() {
+        let mut timer = timer_with_laps(&[]);
+        timer.select_fastest_lap();
+        timer.select_slowest_lap();
+        assert_eq!(timer.laps_list_state.selected(), None);
+    }
+
+    #[test]
+    fn lap_on_resume_records_a_lap_only_when_resuming() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.lap_on_resume = true;
+        run(&mut app, MockEventSource::keys("p"));
+        assert!(!app.timer().stopwatch.is_running());
+        assert!(app.timer().stopwatch.laps().is_empty());
+
+        run(&mut app, MockEventSource::keys("p"));
+        assert!(app.timer().stopwatch.is_running());
+        assert_eq!(app.timer().stopwatch.laps().len(), 1);
+
+        run(&mut app, MockEventSource::keys("p"));
+        assert_eq!(app.timer().stopwatch.laps().len(), 1);
+    }
+
+    #[test]
+    fn no_lap_on_resume_unless_asked_for() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("pp"));
+        assert!(app.timer().stopwatch.laps().is_empty());
+    }
+
+    #[test]
+    fn timer_and_laps_can_use_different_precisions() {
+        let mut app = App::new(vec![timer_with_laps(&[]).stopwatch]);
+        app.precision = Precision::Tenths;
+        app.lap_precision = Precision::Milliseconds;
+        let duration = Duration::from_micros(65_432_100);
+        assert_eq!(app.format_time(duration), "1m 5.4s");
+        assert_eq!(app.format_lap_time(duration), "1m 5.432s");
+
+        let timer = timer_with_laps(&[2, 3]);
+        let rows = lap_rows(
+            &timer.stopwatch,
+            app.lap_precision,
+            app.display_format,
+            &app.theme.palette,
+        );
+        assert_eq!(rows[1].lap_time, "3.000s");
+        assert_eq!(rows[1].split, "5.000s");
+        assert_eq!(app.format_time(timer.stopwatch.elapsed()), "5.0s");
+    }
+
+    #[test]
+    fn segment_laps_count_from_the_last_resume() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        run(&mut app, MockEventSource::keys("  "));
+        assert_eq!(app.timer().segment_laps(), 2);
+        run(&mut app, MockEventSource::keys("pp "));
+        assert_eq!(app.timer().segment_laps(), 1);
+        assert_eq!(app.timer().stopwatch.laps().len(), 3);
+        run(&mut app, MockEventSource::keys("rr"));
+        assert_eq!(app.timer().segment_laps(), 0);
+    }
+
+    #[test]
+    fn the_lap_on_resume_closes_the_old_segment() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.lap_on_resume = true;
+        run(&mut app, MockEventSource::keys("  pp"));
+        assert_eq!(app.timer().stopwatch.laps().len(), 3);
+        assert_eq!(app.timer().segment_laps(), 0);
+    }
+
+    #[test]
+    fn a_paused_timer_isnt_redrawn_without_input() {
+        let mut app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        let frames = run(
+            &mut app,
+            MockEventSource::new(Vec::new()).idle_for(Duration::from_millis(600)),
+        );
+        // The first frame, plus at most one for the clock in the title bar
+        assert!(frames <= 2, "{} frames", frames);
+    }
+
+    #[test]
+    fn ticks_finer_than_the_precision_reuse_the_frame() {
+        let idle = Duration::from_millis(400);
+        let frames_at = |precision| {
+            let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+            app.precision = precision;
+            app.tick_rate = MIN_TICK_RATE;
+            app.second_bar = false;
+            run(&mut app, MockEventSource::new(Vec::new()).idle_for(idle))
+        };
+
+        // About 40 ticks: tenths change only every 10th, milliseconds on all of them
+        let tenths = frames_at(Precision::Tenths);
+        let milliseconds = frames_at(Precision::Milliseconds);
+        assert!(tenths <= 8, "{} frames at tenths", tenths);
+        assert!(
+            milliseconds >= 20,
+            "{} frames at milliseconds",
+            milliseconds
+        );
+    }
+
+    #[test]
+    fn a_start_lap_is_never_the_fastest() {
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.add_start_lap();
+        stopwatch.toggle_pause();
+        stopwatch.add_lap();
+        stopwatch.add_lap();
+        let mut timer = TimerState::new(stopwatch);
+        timer.select_fastest_lap();
+        assert_ne!(timer.selected_lap_index(), Some(0));
+
+        let palette = Theme::default().palette;
+        let rows = lap_rows(
+            &timer.stopwatch,
+            Precision::Hundredths,
+            DisplayFormat::Words,
+            &palette,
+        );
+        assert_eq!(rows[0].lap_style, Style::new().fg(palette.muted));
+        // Lap 2 has no earlier timed lap to compare against
+        assert!(rows[1].delta.is_none());
+        assert!(rows[2].delta.is_some());
+    }
+
+    #[test]
+    fn recent_avg_covers_only_the_latest_laps() {
+        let mut app = App::new(vec![timer_with_laps(&[10, 1, 2, 3, 4, 5]).stopwatch]);
+        app.window = 5;
+        assert_eq!(app.recent_avg(), Some(secs(3)));
+        app.window = 2;
+        assert_eq!(app.recent_avg(), Some(Duration::from_millis(4500)));
+    }
+
+    #[test]
+    fn recent_avg_uses_what_there_is_until_the_window_fills() {
+        let mut app = App::new(vec![timer_with_laps(&[2, 4]).stopwatch]);
+        app.window = 5;
+        assert_eq!(app.recent_avg(), Some(secs(3)));
+
+        let app = App::new(vec![timer_with_laps(&[]).stopwatch]);
+        assert_eq!(app.recent_avg(), None);
+    }
+
+    #[test]
+    fn statistics_wrap_between_items() {
+        let items = ["Laps: 3", "Average: 2.00s", "Best: 1.00s"].map(String::from);
+        assert_eq!(
+            pack_items(&items, 80),
+            ["Laps: 3  •  Average: 2.00s  •  Best: 1.00s"]
+        );
+        assert_eq!(
+            pack_items(&items, 30),
+            ["Laps: 3  •  Average: 2.00s", "Best: 1.00s"]
+        );
+        assert_eq!(pack_items(&items, 5), items);
+    }
+
+    #[test]
+    fn starting_a_paused_offset_isnt_a_resume() {
+        let mut stopwatch = Stopwatch::new_paused(Mode::Stopwatch);
+        stopwatch.add_offset(Duration::from_secs(600)).unwrap();
+        let mut app = test_app(stopwatch);
+        app.lap_on_resume = true;
+        run(&mut app, MockEventSource::keys("p"));
+        assert!(app.timer().stopwatch.is_running());
+        assert!(app.timer().stopwatch.laps().is_empty());
+
+        // Later resumes still lap
+        run(&mut app, MockEventSource::keys("pp"));
+        assert_eq!(app.timer().stopwatch.laps().len(), 1);
+    }
+
+    #[test]
+    fn control_hints_drop_the_least_important_first() {
+        let hints = ["SPACE: Lap", "P: Pause", "R: Reset", "↑↓: Scroll"].map(String::from);
+        let essential = ["?: Help", "Q: Quit"].map(String::from);
+        let all = "SPACE: Lap  •  P: Pause  •  R: Reset  •  ↑↓: Scroll  •  ?: Help  •  Q: Quit";
+        assert_eq!(fit_hints(hints.to_vec(), &essential, 100), all);
+        assert_eq!(
+            fit_hints(hints.to_vec(), &essential, 74),
+            "SPACE: Lap  •  P: Pause  •  R: Reset  •  ?: Help  •  Q: Quit"
+        );
+        assert_eq!(
+            fit_hints(hints.to_vec(), &essential, 10),
+            "?: Help  •  Q: Quit"
+        );
+    }
+
+    #[test]
+    fn laps_survive_a_pomodoro_phase_change() {
+        let mut timer = timer_with_laps(&[1, 2]);
+        let pomodoro = Pomodoro::default();
+        let short_break = pomodoro.short_break;
+        timer.pomodoro = Some(pomodoro);
+        timer.advance_pomodoro();
+        assert_eq!(timer.stopwatch.laps().len(), 2);
+        assert_eq!(
+            timer.stopwatch.mode(),
+            Mode::Countdown {
+                target: short_break
+            }
+        );
+        assert!(timer.stopwatch.is_running());
+    }
+
+    #[test]
+    fn laps_survive_an_interval_round() {
+        let mut timer = timer_with_laps(&[1, 2]);
+        timer.interval = Some(Interval::new(secs(30), Some(3)));
+        timer.advance_interval();
+        assert_eq!(timer.stopwatch.laps().len(), 2);
+        assert!(timer.stopwatch.is_running());
+    }
+
+    #[test]
+    fn undoing_a_reset_restores_the_lap_state() {
+        let mut timer = timer_with_laps(&[1, 2, 3]);
+        timer.resume_lap_count = 2;
+        timer.dirty = true;
+        timer.lap_sort = LapSort::Fastest;
+        timer.reference_lap = Some(1);
+        timer.alarm = Some(secs(90));
+        timer.last_milestone = Some(4);
+        timer.save_undo("reset");
+        timer.reset();
+        timer.dirty = false;
+        timer.lap_sort = LapSort::Chronological;
+        timer.alarm = None;
+        timer.last_milestone = None;
+
+        assert_eq!(timer.undo(), Some("reset"));
+        assert_eq!(timer.stopwatch.laps().len(), 3);
+        assert_eq!(timer.resume_lap_count, 2);
+        assert_eq!(timer.segment_laps(), 1);
+        assert!(timer.dirty);
+        assert!(timer.lap_sort == LapSort::Fastest);
+        assert_eq!(timer.reference_lap, Some(1));
+        assert_eq!(timer.alarm, Some(secs(90)));
+        assert_eq!(timer.last_milestone, Some(4));
+    }
+
+    #[test]
+    fn ignore_suspend_drops_a_long_wait() {
+        let stall = Duration::from_millis(600);
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.tick_rate = Duration::from_millis(50);
+        app.ignore_suspend = true;
+        run(&mut app, MockEventSource::new(Vec::new()).stall_for(stall));
+        assert!(app.timer().stopwatch.elapsed() < Duration::from_millis(400));
+
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.tick_rate = Duration::from_millis(50);
+        run(&mut app, MockEventSource::new(Vec::new()).stall_for(stall));
+        assert!(app.timer().stopwatch.elapsed() >= stall);
+    }
+
+    #[test]
+    fn stats_items_count_laps_above_and_below_average() {
+        let mut app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        app.tabs[0] = timer_with_laps(&[3]);
+        assert!(!stats_items(&app).iter().any(|item| item.contains("above")));
+        app.tabs[0] = timer_with_laps(&[1, 2, 6]);
+        assert!(stats_items(&app).contains(&"1 above / 2 below average".to_string()));
+    }
+
+    #[test]
+    fn stats_items_end_with_the_time_active_and_paused() {
+        let app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        let items = stats_items(&app);
+        assert_eq!(items[0], "No laps recorded yet");
+        assert!(items[1].starts_with("Active: "));
+        assert!(items[2].starts_with("Paused: "));
+    }
+
+    #[test]
+    fn stats_items_end_with_the_personal_best() {
+        let mut app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        app.personal_best = PersonalBest::Laps;
+        assert!(!stats_items(&app).iter().any(|item| item.starts_with("PB")));
+        app.records.most_laps = Some(12);
+        assert_eq!(stats_items(&app).last().unwrap(), "PB: 12 laps");
+    }
+
+    #[test]
+    fn stats_items_include_the_spread_of_lap_times() {
+        let mut app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        app.tabs[0] = timer_with_laps(&[3, 3]);
+        assert!(stats_items(&app).iter().any(|item| item.ends_with("(0%)")));
+        // One lap has no spread to speak of
+        app.tabs[0] = timer_with_laps(&[3]);
+        assert!(stats_items(&app).iter().any(|item| item.ends_with("(—)")));
+    }
+
+    #[test]
+    fn mono_tells_the_fastest_and_slowest_laps_apart() {
+        let timer = timer_with_laps(&[2, 1, 3]);
+        let mut theme = Theme::default();
+        theme.set_palette("mono").unwrap();
+        let rows = lap_rows(
+            &timer.stopwatch,
+            Precision::Hundredths,
+            DisplayFormat::Words,
+            &theme.palette,
+        );
+        let [first, fastest, slowest] = [0, 1, 2].map(|i| &rows[i]);
+        assert!(first.lap_style.add_modifier.is_empty());
+        assert!(fastest.lap_style.add_modifier.contains(Modifier::BOLD));
+        assert!(slowest.lap_style.add_modifier.contains(Modifier::REVERSED));
+        // Faster and slower deltas differ too
+        assert_ne!(
+            fastest.delta.as_ref().unwrap().1,
+            slowest.delta.as_ref().unwrap().1
+        );
+    }
+
+    #[test]
+    fn a_reset_unfreezes_the_display() {
+        let mut timer = timer_with_laps(&[1, 2]);
+        timer.frozen = Some(secs(3));
+        timer.reset();
+        assert_eq!(timer.frozen, None);
+        assert!(timer.shown_time() < secs(1));
+    }
+
+    #[test]
+    fn stats_total_uses_the_lap_precision() {
+        let mut app = test_app(Stopwatch::new_paused(Mode::Stopwatch));
+        app.tabs[0] = timer_with_laps(&[1, 2]);
+        app.precision = Precision::Tenths;
+        app.lap_precision = Precision::Milliseconds;
+        assert!(stats_items(&app).contains(&"Total: 3.000s".to_string()));
+    }
+
+    #[test]
+    fn the_second_bar_redraws_only_when_a_cell_fills() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.precision = Precision::Tenths;
+        app.tick_rate = MIN_TICK_RATE;
+        let idle = MockEventSource::new(Vec::new()).idle_for(Duration::from_millis(400));
+        // A 12-cell bar fills a cell about every 83ms, on top of the tenths changing
+        let frames = run_sized(&mut app, idle, 20, 40);
+        assert_eq!(app.second_bar_cells, 12);
+        assert!(frames <= 12, "{} frames", frames);
+    }
+
+    #[test]
+    fn a_frozen_display_stands_still() {
+        let mut app = test_app(Stopwatch::new(Mode::Stopwatch));
+        app.precision = Precision::Milliseconds;
+        app.tick_rate = MIN_TICK_RATE;
+        app.tabs[0].frozen = Some(secs(1));
+        let frames = run(
+            &mut app,
+            MockEventSource::new(Vec::new()).idle_for(Duration::from_millis(400)),
+        );
+        assert_eq!(app.tabs[0].second_bar_ratio(), None);
+        // The first frame, plus at most one for the clock in the title bar
+        assert!(frames <= 2, "{} frames", frames);
     }
 }